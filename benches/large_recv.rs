@@ -0,0 +1,39 @@
+//! Average cost of receiving (and reading) a 512-byte item, the access
+//! pattern the `prefetch` feature targets. Compare:
+//!
+//! ```text
+//! cargo bench --bench large_recv
+//! cargo bench --bench large_recv --features prefetch
+//! ```
+
+use llt_rs::RingBuffer;
+use std::hint;
+use std::time::{Duration, Instant};
+
+/// With 512-byte items that is 2 MiB, more than most L2 caches, so the
+/// consumer reads cold-ish memory.
+const SLOTS: usize = 4096;
+const ROUNDS: usize = 200;
+
+fn main() {
+    let rb = RingBuffer::new(SLOTS);
+    let mut elapsed = Duration::ZERO;
+    for round in 0..ROUNDS {
+        for i in 0..SLOTS {
+            let _ = rb.send([(round + i) as u64; 64]);
+        }
+
+        let start = Instant::now();
+        let mut sum = 0u64;
+        while let Some(item) = rb.recv() {
+            sum = sum.wrapping_add(item.iter().fold(0, |acc: u64, x| acc.wrapping_add(*x)));
+        }
+        elapsed += start.elapsed();
+        hint::black_box(sum);
+    }
+
+    println!(
+        "Average 512-byte recv: {:?}",
+        elapsed.div_f64((ROUNDS * SLOTS) as f64)
+    );
+}
@@ -1,6 +1,8 @@
 #![doc = include_str!("README.md")]
 
 use core_affinity;
+use std::fmt;
+use std::marker::PhantomData;
 
 ///A unique identifier for a CPU core.
 
@@ -12,6 +14,38 @@ pub struct CoreId {
     internal: usize,
 }
 
+impl CoreId {
+    /// Creates a `CoreId` from a raw processor id (e.g. read from config).
+    ///
+    /// The id is not checked here; [`pin_to_core`] rejects ids that don't
+    /// exist on this machine.
+    pub fn new(id: usize) -> Self {
+        Self { id, internal: id }
+    }
+}
+
+/// The error returned by [`pin_to_core`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AffinityError {
+    /// The core id does not exist on this machine.
+    InvalidCore(usize),
+    /// The core exists, but the OS refused (or doesn't support) the pin.
+    PinFailed(usize),
+}
+
+impl fmt::Display for AffinityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AffinityError::InvalidCore(id) => {
+                write!(f, "core {} does not exist on this machine", id)
+            }
+            AffinityError::PinFailed(id) => write!(f, "failed to pin thread to core {}", id),
+        }
+    }
+}
+
+impl std::error::Error for AffinityError {}
+
 ///Retrieves a list of all available CPU processor IDs on the system.
 pub fn get_core_ids() -> Vec<CoreId> {
     //If the feature is disabled or the crate fails to load, return empty.
@@ -27,16 +61,176 @@ pub fn get_core_ids() -> Vec<CoreId> {
 }
 /// Pins the *current* thread to the specified CPU core.
 ///
-/// Returns `true` if the operation was successful.
+/// Returns [`AffinityError::InvalidCore`] if `core_id` does not exist on
+/// this machine, and [`AffinityError::PinFailed`] if the OS refuses
+/// (e.g. on macOS, or in a restricted container).
 ///
 /// # Important
 /// Once pinned, the OS will try very hard to keep this thread on that core.
 /// You should ensure that no other heavy threads are competing for this core.
-pub fn pin_to_core(core_id: CoreId) -> bool {
+pub fn pin_to_core(core_id: CoreId) -> Result<(), AffinityError> {
+    if !online_cores().contains(&core_id) {
+        return Err(AffinityError::InvalidCore(core_id.id));
+    }
     let internal_core = core_affinity::CoreId {
         id: core_id.internal,
     };
-    core_affinity::set_for_current(internal_core)
+    if core_affinity::set_for_current(internal_core) {
+        Ok(())
+    } else {
+        Err(AffinityError::PinFailed(core_id.id))
+    }
+}
+
+/// Returns the cores that are currently online, regardless of the calling
+/// thread's affinity mask.
+///
+/// On Linux this reads `/sys/devices/system/cpu/online`, so cores taken
+/// offline (e.g. for power management) are excluded. Note the contrast with
+/// [`get_core_ids`], which lists the cores the *current thread* may run on:
+/// in a restricted cpuset, or once pinned, that is a subset of this.
+/// Elsewhere (or if the file is unreadable) it falls back to `get_core_ids`.
+pub fn online_cores() -> Vec<CoreId> {
+    #[cfg(target_os = "linux")]
+    if let Ok(list) = std::fs::read_to_string("/sys/devices/system/cpu/online") {
+        return parse_cpu_list(&list).into_iter().map(CoreId::new).collect();
+    }
+    get_core_ids()
+}
+
+/// Returns the core the calling thread is running on right now, if the
+/// platform can tell (Linux only). Without pinning, this may change at
+/// any moment.
+pub fn get_current_core() -> Option<CoreId> {
+    #[cfg(target_os = "linux")]
+    {
+        // SAFETY: `sched_getcpu` has no preconditions.
+        let cpu = unsafe { libc::sched_getcpu() };
+        usize::try_from(cpu).ok().map(CoreId::new)
+    }
+    #[cfg(not(target_os = "linux"))]
+    None
+}
+
+/// Pins the current thread to `core` until the returned guard is dropped,
+/// at which point the thread's previous affinity mask is restored.
+///
+/// This keeps phase-scoped pinning correct even on early returns or panics.
+/// Restoring the mask is only supported on Linux; elsewhere the guard does
+/// nothing on drop.
+pub fn pin_scoped(core: CoreId) -> Result<AffinityGuard, AffinityError> {
+    let saved = current_mask();
+    pin_to_core(core)?;
+    Ok(AffinityGuard {
+        saved,
+        _not_send: PhantomData,
+    })
+}
+
+/// Runs `f` with the current thread pinned to `core`, then restores the
+/// previous affinity mask (also if `f` panics) and returns `f`'s result.
+///
+/// Pinning is best effort: if it fails (e.g. on macOS), `f` still runs,
+/// just unpinned. Use [`pin_scoped`] if you need to know.
+pub fn with_pinned<R>(core: CoreId, f: impl FnOnce() -> R) -> R {
+    let _guard = pin_scoped(core);
+    f()
+}
+
+/// Restores the thread's original affinity mask when dropped.
+/// Created by [`pin_scoped`].
+///
+/// Not `Send`: it must be dropped on the thread it was created on.
+pub struct AffinityGuard {
+    #[cfg(target_os = "linux")]
+    saved: Option<libc::cpu_set_t>,
+    #[cfg(not(target_os = "linux"))]
+    saved: Option<()>,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for AffinityGuard {
+    fn drop(&mut self) {
+        #[cfg(target_os = "linux")]
+        if let Some(mask) = &self.saved {
+            // SAFETY: `mask` is a valid set captured by `sched_getaffinity`;
+            // pid 0 means the calling thread.
+            unsafe {
+                libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), mask);
+            }
+        }
+    }
+}
+
+/// Captures the calling thread's affinity mask.
+#[cfg(target_os = "linux")]
+fn current_mask() -> Option<libc::cpu_set_t> {
+    // SAFETY: An all-zero `cpu_set_t` is a valid (empty) set, and
+    // `sched_getaffinity` writes at most `size_of::<cpu_set_t>()` bytes.
+    unsafe {
+        let mut mask: libc::cpu_set_t = std::mem::zeroed();
+        (libc::sched_getaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &mut mask) == 0)
+            .then_some(mask)
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn current_mask() -> Option<()> {
+    None
+}
+
+/// Returns the other logical cores that share a physical core with `core`
+/// (its SMT / Hyper-Threading siblings).
+///
+/// Useful for reserving a whole physical core: pin your hot thread to `core`
+/// and keep its siblings idle.
+///
+/// Returns an empty `Vec` if the core has no siblings (SMT disabled) or the
+/// topology is unavailable. Only implemented on Linux, where it reads
+/// `/sys/devices/system/cpu/cpuN/topology/thread_siblings_list`.
+pub fn sibling_cores(core: CoreId) -> Vec<CoreId> {
+    #[cfg(target_os = "linux")]
+    {
+        let path = format!(
+            "/sys/devices/system/cpu/cpu{}/topology/thread_siblings_list",
+            core.internal
+        );
+        let Ok(list) = std::fs::read_to_string(path) else {
+            return Vec::new();
+        };
+        parse_cpu_list(&list)
+            .into_iter()
+            .filter(|&id| id != core.internal)
+            .map(|id| CoreId { id, internal: id })
+            .collect()
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = core;
+        Vec::new()
+    }
+}
+
+/// Parses the kernel's CPU list format (e.g. `"0-3,8,10-11"`) into ids.
+/// Malformed entries are skipped.
+#[cfg_attr(not(any(target_os = "linux", test)), allow(dead_code))]
+fn parse_cpu_list(list: &str) -> Vec<usize> {
+    let mut ids = Vec::new();
+    for part in list.trim().split(',').filter(|p| !p.is_empty()) {
+        match part.split_once('-') {
+            Some((start, end)) => {
+                if let (Ok(start), Ok(end)) = (start.parse::<usize>(), end.parse::<usize>()) {
+                    ids.extend(start..=end);
+                }
+            }
+            None => {
+                if let Ok(id) = part.parse() {
+                    ids.push(id);
+                }
+            }
+        }
+    }
+    ids
 }
 
 #[cfg(test)]
@@ -51,17 +245,30 @@ mod tests {
         println!("Found {} cores", cores.len());
     }
 
+    #[test]
+    fn test_online_cores() {
+        let online = online_cores();
+        assert!(!online.is_empty());
+        // A thread can only be allowed on cores that are online. (The reverse
+        // doesn't hold in a restricted cpuset or after pinning.)
+        for core in get_core_ids() {
+            assert!(online.contains(&core), "{:?} is not online", core);
+        }
+    }
+
     #[test]
     fn test_pinning() {
         let cores = get_core_ids();
         if let Some(core) = cores.first() {
             let core = *core;
             let handle = thread::spawn(move || {
-                let success = pin_to_core(core);
-                if !success {
-                    eprintln!("WARNING: Failed to pin thread (common in containers/CI)")
-                } else {
-                    println!("Successfully pinned to core {}", core.id);
+                match pin_to_core(core) {
+                    Ok(()) => println!("Successfully pinned to core {}", core.id),
+                    Err(e) => {
+                        // Common in containers/CI, but the core itself is valid.
+                        assert_eq!(e, AffinityError::PinFailed(core.id));
+                        eprintln!("WARNING: {}", e)
+                    }
                 }
                 let mut x = 0;
                 for _ in 0..1000 {
@@ -72,4 +279,119 @@ mod tests {
             assert_eq!(handle.join().unwrap(), 1000);
         }
     }
+
+    #[test]
+    fn test_pin_to_invalid_core() {
+        let bogus = CoreId::new(usize::MAX);
+        let err = thread::spawn(move || pin_to_core(bogus))
+            .join()
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(err, AffinityError::InvalidCore(usize::MAX));
+    }
+
+    #[test]
+    fn test_pin_scoped_restores_mask() {
+        thread::spawn(|| {
+            let before = get_core_ids();
+            let core = *before.last().unwrap();
+            {
+                let _guard = match pin_scoped(core) {
+                    Ok(guard) => guard,
+                    Err(e) => {
+                        eprintln!("WARNING: {} (common in containers/CI)", e);
+                        return;
+                    }
+                };
+                assert_eq!(get_core_ids(), vec![core]);
+                #[cfg(target_os = "linux")]
+                assert_eq!(get_current_core(), Some(core));
+            }
+            assert_eq!(get_core_ids(), before);
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn test_with_pinned_returns_and_restores() {
+        thread::spawn(|| {
+            let before = get_core_ids();
+            let core = before[0];
+            let (inside, result) = with_pinned(core, || (get_core_ids(), 21 * 2));
+            assert_eq!(result, 42);
+            // Pinned, or (where pinning is unsupported) left alone.
+            assert!(inside == vec![core] || inside == before);
+            assert_eq!(get_core_ids(), before);
+
+            // Restored on unwind too.
+            let panicked = std::panic::catch_unwind(|| with_pinned(core, || panic!("boom")));
+            assert!(panicked.is_err());
+            assert_eq!(get_core_ids(), before);
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn test_repin_to_another_core() {
+        let cores = get_core_ids();
+        if cores.len() < 2 {
+            return;
+        }
+        let (first, second) = (cores[0], cores[1]);
+        thread::spawn(move || {
+            if pin_to_core(first).is_err() {
+                return; // Pinning is unavailable here.
+            }
+            // Our mask is now just `first`, but `second` still exists.
+            assert_ne!(
+                pin_to_core(second),
+                Err(AffinityError::InvalidCore(second.id))
+            );
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn test_parse_cpu_list() {
+        assert_eq!(parse_cpu_list("0\n"), vec![0]);
+        assert_eq!(parse_cpu_list("0,4"), vec![0, 4]);
+        assert_eq!(parse_cpu_list("0-3,8,10-11"), vec![0, 1, 2, 3, 8, 10, 11]);
+        assert!(parse_cpu_list("").is_empty());
+    }
+
+    #[test]
+    fn test_sibling_cores() {
+        // Siblings come from the machine's topology, not from this thread's
+        // cpuset, so check them against every online core.
+        let Ok(list) = std::fs::read_to_string("/sys/devices/system/cpu/online") else {
+            // No sysfs (non-Linux): there is no topology to report.
+            assert!(get_core_ids().iter().all(|&c| sibling_cores(c).is_empty()));
+            return;
+        };
+        let online = parse_cpu_list(&list);
+        let mut with_siblings = 0;
+        for &id in &online {
+            let core = CoreId { id, internal: id };
+            let siblings = sibling_cores(core);
+            // A core is never its own sibling, and siblings are online cores.
+            assert!(!siblings.contains(&core));
+            for sibling in &siblings {
+                assert!(
+                    online.contains(&sibling.internal),
+                    "{sibling:?} is not online"
+                );
+            }
+            if !siblings.is_empty() {
+                with_siblings += 1;
+            }
+        }
+        // With SMT active, some core must report a sibling other than itself.
+        let smt = std::fs::read_to_string("/sys/devices/system/cpu/smt/active");
+        if smt.is_ok_and(|s| s.trim() == "1") {
+            assert!(with_siblings > 0, "SMT is on but no core has a sibling");
+        }
+    }
 }
@@ -1,8 +1,26 @@
 #![doc = include_str!("README.md")]
 
-use std::cell::UnsafeCell;
+mod pool;
+pub use pool::{ArenaPool, PooledArena};
+#[cfg(feature = "serde")]
+mod snapshot;
+#[cfg(feature = "serde")]
+pub use snapshot::ArenaSnapshot;
+
+use crate::error::Error;
+use std::alloc::Layout;
+use std::cell::{RefCell, UnsafeCell};
+use std::marker::PhantomData;
 use std::mem;
-use std::ptr;
+use std::ops::{Deref, DerefMut};
+use std::ptr::{self, NonNull};
+
+/// The largest alignment [`Arena::extend_from`] preserves.
+pub const MERGE_ALIGN: usize = 16;
+
+/// The stride of [`Arena::prewarm`]: the smallest common page size, so
+/// larger pages get touched too.
+const PREWARM_STRIDE: usize = 4096;
 
 /// A fast, linear bump allocator.
 ///
@@ -12,6 +30,30 @@ pub struct Arena {
     buffer: Box<[u8]>,
     /// The current offset into the buffer (the "bump pointer").
     offset: UnsafeCell<usize>,
+    /// Cleanup closures registered with `defer`, run on `reset`/`Drop`.
+    deferred: RefCell<Vec<Box<dyn FnOnce() + Send>>>,
+    /// Bumped whenever earlier allocations become invalid (`reset`, `grow_to`).
+    #[cfg(debug_assertions)]
+    generation: u64,
+}
+
+/// A raw allocation from [`Arena::alloc_raw`], tagged (in debug builds)
+/// with the arena generation it belongs to.
+///
+/// Pass it to [`Arena::validate`] before dereferencing to catch pointers
+/// held across a `reset`. In release builds it is just a pointer.
+#[derive(Clone, Copy, Debug)]
+pub struct RawHandle {
+    ptr: NonNull<u8>,
+    #[cfg(debug_assertions)]
+    generation: u64,
+}
+
+impl RawHandle {
+    /// Returns the allocation's address.
+    pub fn as_ptr(&self) -> NonNull<u8> {
+        self.ptr
+    }
 }
 
 impl Arena {
@@ -23,6 +65,9 @@ impl Arena {
         Self {
             buffer,
             offset: UnsafeCell::new(0),
+            deferred: RefCell::new(Vec::new()),
+            #[cfg(debug_assertions)]
+            generation: 0,
         }
     }
 
@@ -37,9 +82,94 @@ impl Arena {
     /// fixed-size arrays, or simple POD structs).
     #[allow(clippy::mut_from_ref)]
     pub fn alloc<T>(&self, value: T) -> &mut T {
-        let size = mem::size_of::<T>();
-        let align = mem::align_of::<T>();
+        match self.alloc_err(value) {
+            Ok(slot) => slot,
+            Err(_) => panic!(
+                "Arena OOM: Capacity {} bytes, requested {} bytes",
+                self.buffer.len(),
+                self.used_bytes() + mem::size_of::<T>()
+            ),
+        }
+    }
+
+    /// Like [`alloc`](Self::alloc), but returns [`Error::OutOfMemory`]
+    /// instead of panicking when the arena is full.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_err<T>(&self, value: T) -> Result<&mut T, Error> {
+        let ptr = self
+            .try_alloc_layout(Layout::new::<T>())
+            .ok_or(Error::OutOfMemory)?
+            .cast::<T>();
+
+        // SAFETY: `try_alloc_layout` returned a fresh region sized and
+        // aligned for `T` that nothing else references.
+        unsafe {
+            ptr::write(ptr.as_ptr(), value);
+            Ok(&mut *ptr.as_ptr())
+        }
+    }
 
+    /// Allocates uninitialized space for `layout` and returns a pointer to it.
+    ///
+    /// This is the lowest-level primitive, for callers that already compute
+    /// a [`Layout`] (type-erased storage, FFI). The alignment is honoured
+    /// against the buffer's real address, not just the offset into it.
+    ///
+    /// # Panics
+    /// Panics if the arena runs out of space.
+    pub fn alloc_layout(&self, layout: Layout) -> NonNull<u8> {
+        match self.try_alloc_layout(layout) {
+            Some(ptr) => ptr,
+            None => panic!(
+                "Arena OOM: Capacity {} bytes, requested {} bytes",
+                self.buffer.len(),
+                self.used_bytes().saturating_add(layout.size())
+            ),
+        }
+    }
+
+    /// Like [`alloc_layout`](Self::alloc_layout), but returns a
+    /// [`RawHandle`] that [`validate`](Self::validate) can check later.
+    ///
+    /// # Panics
+    /// Panics if the arena runs out of space.
+    pub fn alloc_raw(&self, layout: Layout) -> RawHandle {
+        RawHandle {
+            ptr: self.alloc_layout(layout),
+            #[cfg(debug_assertions)]
+            generation: self.generation,
+        }
+    }
+
+    /// Asserts (in debug builds) that `handle` is still live: no `reset` or
+    /// `grow_to` happened since it was allocated, and it came from this arena.
+    ///
+    /// Compiles to nothing in release builds.
+    ///
+    /// # Panics
+    /// In debug builds, panics if the handle is stale or foreign.
+    pub fn validate(&self, handle: &RawHandle) {
+        #[cfg(debug_assertions)]
+        {
+            assert_eq!(
+                handle.generation, self.generation,
+                "Arena: raw handle used after reset (generation {} != {})",
+                handle.generation, self.generation
+            );
+            let addr = handle.ptr.as_ptr() as usize;
+            let start = self.buffer.as_ptr() as usize;
+            assert!(
+                (start..=start + self.buffer.len()).contains(&addr),
+                "Arena: raw handle belongs to another arena"
+            );
+        }
+        #[cfg(not(debug_assertions))]
+        let _ = handle;
+    }
+
+    /// Bumps the offset past a region fitting `layout`, or returns `None`
+    /// (leaving the arena untouched) if it doesn't fit.
+    fn try_alloc_layout(&self, layout: Layout) -> Option<NonNull<u8>> {
         // We need to modify the offset, but we want to allow shared (&self) access
         // so we can allocate multiple things "simultaneously" (conceptually).
         // In a single-threaded LOB context, this effectively allows
@@ -48,38 +178,152 @@ impl Arena {
         // We haven't marked it Sync, so we are good.
         let current_offset = unsafe { *self.offset.get() };
 
-        // Calculate padding needed to satisfy alignment requirements
-        let padding = (align - (current_offset % align)) % align;
-        let start = current_offset + padding;
-        let end = start + size;
+        // Pad the real address, not the offset: the buffer itself is only
+        // guaranteed to be byte aligned.
+        let base = self.buffer.as_ptr() as usize;
+        let start = (base + current_offset).checked_next_multiple_of(layout.align())? - base;
+        let end = start.checked_add(layout.size())?;
 
         if end > self.buffer.len() {
-            panic!(
-                "Arena OOM: Capacity {} bytes, requested {} bytes",
-                self.buffer.len(),
-                end
-            );
+            return None;
         }
 
+        // SAFETY: `start <= end <= len`, so the pointer is in bounds (or one
+        // past the end for a zero-sized layout), and the arena is not `Sync`.
         unsafe {
-            // 1. Get the pointer to the destination
-            let ptr = self.buffer.as_ptr().add(start) as *mut T;
+            *self.offset.get() = end;
+            Some(NonNull::new_unchecked(
+                self.buffer.as_ptr().add(start) as *mut u8
+            ))
+        }
+    }
 
-            // 2. Write the value
-            ptr::write(ptr, value);
+    /// Allocates a zeroed, byte-aligned region of `len` bytes (e.g. I/O scratch space).
+    ///
+    /// The region is explicitly zeroed: after a `reset` it may hold bytes
+    /// from a previous cycle.
+    ///
+    /// # Panics
+    /// Panics if the arena runs out of space (or `len` overflows `isize`).
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_bytes(&self, len: usize) -> &mut [u8] {
+        let layout = Layout::array::<u8>(len).expect("Arena: byte length overflows isize");
+        let ptr = self.alloc_layout(layout).as_ptr();
 
-            // 3. Bump the pointer
-            *self.offset.get() = end;
+        // SAFETY: `alloc_layout` returned `len` fresh in-bounds bytes.
+        unsafe {
+            ptr::write_bytes(ptr, 0, len);
+            std::slice::from_raw_parts_mut(ptr, len)
+        }
+    }
+
+    /// Copies `other`'s used bytes to the end of this arena, e.g. to gather
+    /// per-worker arenas into one contiguous block.
+    ///
+    /// Only meaningful for self-contained POD data: the bytes are copied
+    /// verbatim, so pointers *into* `other` still point into `other`, and
+    /// `other`'s deferred closures are not transferred. The copy is placed
+    /// so that anything aligned to up to [`MERGE_ALIGN`] bytes in `other`
+    /// stays aligned. Fails with [`Error::OutOfMemory`] (leaving this arena
+    /// untouched) if it doesn't fit.
+    pub fn extend_from(&mut self, other: &Arena) -> Result<(), Error> {
+        let len = other.used_bytes();
+        let base = self.buffer.as_ptr() as usize;
+        let offset = self.used_bytes();
+
+        // Match `other`'s base address modulo MERGE_ALIGN, so each value
+        // keeps the alignment it had there.
+        let misalign = (other.buffer.as_ptr() as usize).wrapping_sub(base + offset) % MERGE_ALIGN;
+        let start = offset + misalign;
+        let end = start.checked_add(len).ok_or(Error::OutOfMemory)?;
+        if end > self.buffer.len() {
+            return Err(Error::OutOfMemory);
+        }
+
+        self.buffer[start..end].copy_from_slice(&other.buffer[..len]);
+        *self.offset.get_mut() = end;
+        Ok(())
+    }
+
+    /// Enlarges the arena to `new_capacity` bytes, keeping what was allocated.
+    ///
+    /// The used bytes (and deferred closures) move to a new, larger buffer,
+    /// placed like [`extend_from`](Self::extend_from) does, so values keep
+    /// alignments of up to [`MERGE_ALIGN`]. Taking `&mut self` guarantees no
+    /// reference into the old buffer is still alive. Does nothing if the
+    /// arena is already that big. Fails with [`Error::OutOfMemory`] (leaving
+    /// the arena untouched) if the buffer can't be allocated, or if the
+    /// alignment padding (under `MERGE_ALIGN` bytes) doesn't fit.
+    pub fn grow_to(&mut self, new_capacity: usize) -> Result<(), Error> {
+        if new_capacity <= self.buffer.len() {
+            return Ok(());
+        }
+        let mut buffer = Vec::new();
+        buffer
+            .try_reserve_exact(new_capacity)
+            .map_err(|_| Error::OutOfMemory)?;
+        buffer.resize(new_capacity, 0);
+
+        let mut grown = Self {
+            buffer: buffer.into_boxed_slice(),
+            offset: UnsafeCell::new(0),
+            deferred: RefCell::new(Vec::new()),
+            // The old buffer goes away: its raw handles are stale.
+            #[cfg(debug_assertions)]
+            generation: self.generation + 1,
+        };
+        grown.extend_from(self)?;
+        mem::swap(grown.deferred.get_mut(), self.deferred.get_mut());
+        // The old arena now has nothing left to run on drop.
+        *self = grown;
+        Ok(())
+    }
+
+    /// Allocates a value in the arena and returns an owning [`ArenaBox`].
+    ///
+    /// Unlike [`Arena::alloc`], the value's destructor *does* run, as soon
+    /// as the `ArenaBox` is dropped. The bytes it occupied stay claimed
+    /// until the next `reset`.
+    ///
+    /// # Panics
+    /// Panics if the arena runs out of space.
+    pub fn alloc_owned<T>(&self, value: T) -> ArenaBox<'_, T> {
+        let slot = self.alloc(value);
+        ArenaBox {
+            ptr: NonNull::from(slot),
+            _marker: PhantomData,
+        }
+    }
 
-            // 4. Return the mutable reference
-            &mut *ptr
+    /// Registers a closure to run on the next `reset` (or when the arena
+    /// is dropped).
+    ///
+    /// Useful for the occasional teardown (unmapping a region, releasing a
+    /// refcount) in an otherwise-POD arena. Closures run in *reverse*
+    /// registration order, like destructors at the end of a scope. They
+    /// must be `Send`, so the arena itself can still move between threads.
+    pub fn defer(&self, f: impl FnOnce() + Send + 'static) {
+        self.deferred.borrow_mut().push(Box::new(f));
+    }
+
+    /// Runs (and clears) every deferred closure, newest first.
+    fn run_deferred(&mut self) {
+        let deferred = std::mem::take(self.deferred.get_mut());
+        for f in deferred.into_iter().rev() {
+            f();
         }
     }
 
     /// Resets the arena, effectively freeing all objects at once.
     ///
     /// Note: Destructors (`Drop`) for allocated objects are NOT called.
+    /// Closures registered with [`Arena::defer`] are.
     pub fn reset(&mut self) {
+        self.run_deferred();
+        #[cfg(debug_assertions)]
+        {
+            self.generation += 1;
+        }
         // We require &mut self here to ensure no one else is holding
         // a reference to an allocated object.
         unsafe {
@@ -96,6 +340,86 @@ impl Arena {
     pub fn capacity(&self) -> usize {
         self.buffer.len()
     }
+
+    /// Returns true if `ptr` points into this arena's backing buffer.
+    ///
+    /// This is a plain address-range check, meant for debug assertions. It
+    /// says nothing about whether the pointee is still live (a pointer
+    /// obtained before a `reset` is still "contained").
+    pub fn contains<T>(&self, ptr: *const T) -> bool {
+        let start = self.buffer.as_ptr() as usize;
+        let end = start + self.buffer.len();
+        (start..end).contains(&(ptr as usize))
+    }
+
+    /// Returns the fraction of the arena in use, from `0.0` to `1.0`.
+    ///
+    /// A zero-capacity arena reports `0.0`.
+    pub fn utilization(&self) -> f32 {
+        if self.capacity() == 0 {
+            return 0.0;
+        }
+        self.used_bytes() as f32 / self.capacity() as f32
+    }
+
+    /// Touches every page of the unused part of the buffer, so the OS maps
+    /// them now instead of on the first allocation that lands there.
+    ///
+    /// `vec![0u8; n]` is usually backed by lazily mapped zero pages, so the
+    /// first write to each page takes a minor fault. Call this once after
+    /// `new`, off the hot path. Bytes that already hold allocations are
+    /// left alone.
+    pub fn prewarm(&self) {
+        let base = self.buffer.as_ptr() as *mut u8;
+        let mut offset = self.used_bytes();
+        while offset < self.buffer.len() {
+            // SAFETY: `offset` is in bounds and at or past the bump pointer,
+            // so no reference handed out by the arena covers this byte.
+            unsafe { ptr::write_volatile(base.add(offset), 0) };
+            offset = (offset / PREWARM_STRIDE + 1) * PREWARM_STRIDE;
+        }
+    }
+}
+
+impl Drop for Arena {
+    fn drop(&mut self) {
+        self.run_deferred();
+    }
+}
+
+/// An owning handle to a value stored inside an [`Arena`].
+///
+/// Created by [`Arena::alloc_owned`]. It borrows the arena, so the arena
+/// cannot be reset while the box is alive. Dropping the box runs `T`'s
+/// destructor in place; the memory itself is reclaimed on `reset`.
+pub struct ArenaBox<'a, T> {
+    ptr: NonNull<T>,
+    // We logically own a `T` and borrow the arena it lives in.
+    _marker: PhantomData<(&'a Arena, T)>,
+}
+
+impl<'a, T> Deref for ArenaBox<'a, T> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: `ptr` came from `alloc`, is initialized, and stays valid
+        // for `'a` because the arena cannot be reset while we borrow it.
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<'a, T> DerefMut for ArenaBox<'a, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // SAFETY: See `deref`. We are the only handle to this value.
+        unsafe { self.ptr.as_mut() }
+    }
+}
+
+impl<'a, T> Drop for ArenaBox<'a, T> {
+    fn drop(&mut self) {
+        // SAFETY: The value is initialized and this is the only place that
+        // drops it. The arena will never touch these bytes again until reset.
+        unsafe { ptr::drop_in_place(self.ptr.as_ptr()) }
+    }
 }
 
 #[cfg(test)]
@@ -144,6 +468,254 @@ mod tests {
         assert_eq!(*event3, 123);
     }
 
+    #[test]
+    fn test_alloc_owned_runs_drop() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static DROP_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+        struct Dropper(u32);
+        impl Drop for Dropper {
+            fn drop(&mut self) {
+                DROP_COUNT.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let mut arena = Arena::new(1024);
+        {
+            let mut boxed = arena.alloc_owned(Dropper(7));
+            boxed.0 += 1;
+            assert_eq!(boxed.0, 8);
+            assert_eq!(DROP_COUNT.load(Ordering::SeqCst), 0);
+        } // `boxed` dropped here, before any reset.
+
+        assert_eq!(DROP_COUNT.load(Ordering::SeqCst), 1);
+        // The memory is still claimed until reset.
+        assert!(arena.used_bytes() > 0);
+
+        arena.reset();
+        assert_eq!(DROP_COUNT.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_defer_runs_on_reset_in_reverse_order() {
+        use std::sync::{Arc, Mutex};
+
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let mut arena = Arena::new(64);
+        for name in ["first", "second"] {
+            let order = order.clone();
+            arena.defer(move || order.lock().unwrap().push(name));
+        }
+        assert!(order.lock().unwrap().is_empty());
+
+        arena.reset();
+        assert_eq!(*order.lock().unwrap(), vec!["second", "first"]);
+
+        // Each closure runs once; dropping the arena runs only new ones.
+        let late = order.clone();
+        arena.defer(move || late.lock().unwrap().push("on drop"));
+        drop(arena);
+        assert_eq!(*order.lock().unwrap(), vec!["second", "first", "on drop"]);
+    }
+
+    #[test]
+    fn test_arena_is_send() {
+        fn assert_send<T: Send>() {}
+        // Deferred closures must not stop an arena moving to a worker thread.
+        assert_send::<Arena>();
+    }
+
+    #[test]
+    fn test_utilization() {
+        let arena = Arena::new(16);
+        assert_eq!(arena.utilization(), 0.0);
+
+        arena.alloc(0u64);
+        assert_eq!(arena.utilization(), 0.5);
+
+        arena.alloc(0u64);
+        assert_eq!(arena.utilization(), 1.0);
+
+        assert_eq!(Arena::new(0).utilization(), 0.0);
+    }
+
+    #[test]
+    fn test_alloc_bytes_is_zeroed_after_reset() {
+        let mut arena = Arena::new(256);
+        let bytes = arena.alloc_bytes(128);
+        assert_eq!(bytes.len(), 128);
+        assert!(bytes.iter().all(|&b| b == 0));
+        bytes.fill(0xAB);
+
+        arena.reset();
+        let bytes = arena.alloc_bytes(128);
+        assert!(bytes.iter().all(|&b| b == 0));
+        assert_eq!(arena.used_bytes(), 128);
+    }
+
+    #[test]
+    fn test_contains() {
+        let arena = Arena::new(64);
+        let inside = arena.alloc(42u64);
+        assert!(arena.contains(inside as *const u64));
+
+        let on_stack = 42u64;
+        assert!(!arena.contains(&on_stack as *const u64));
+
+        let other = Arena::new(64);
+        assert!(!other.contains(inside as *const u64));
+    }
+
+    #[test]
+    fn test_no_fragmentation() {
+        // A bump allocator never leaves holes other than alignment padding:
+        // every allocation starts right after the previous one (plus < align
+        // bytes of padding), and `used_bytes` is exactly where the last one ends.
+        let arena = Arena::new(4096);
+        let base = arena.buffer.as_ptr() as usize;
+        let mut prev_end = 0;
+
+        // Returns (start offset, end offset, align) of the allocation.
+        fn footprint<T>(base: usize, r: &T) -> (usize, usize, usize) {
+            let start = r as *const T as usize - base;
+            (start, start + mem::size_of::<T>(), mem::align_of::<T>())
+        }
+
+        for round in 0..20u8 {
+            let allocations = [
+                footprint(base, arena.alloc(round)),
+                footprint(base, arena.alloc(round as u64)),
+                footprint(base, arena.alloc([round; 3])),
+                footprint(base, arena.alloc(round as u32)),
+                footprint(base, arena.alloc(round as u16)),
+                footprint(base, arena.alloc(round as u128)),
+            ];
+            for (start, end, align) in allocations {
+                assert!(start >= prev_end);
+                assert!(start - prev_end < align, "gap larger than padding");
+                prev_end = end;
+            }
+            assert_eq!(arena.used_bytes(), prev_end);
+        }
+    }
+
+    #[test]
+    fn test_alloc_layout_respects_alignment() {
+        let arena = Arena::new(1024);
+        // Knock the offset off any natural boundary first.
+        arena.alloc(1u8);
+
+        for align in [1, 8, 64, 16, 256] {
+            let layout = Layout::from_size_align(3, align).unwrap();
+            let ptr = arena.alloc_layout(layout);
+            assert_eq!(ptr.as_ptr() as usize % align, 0, "align {}", align);
+            assert!(arena.contains(ptr.as_ptr()));
+        }
+    }
+
+    #[test]
+    fn test_extend_from_copies_used_bytes() {
+        let mut parent = Arena::new(256);
+        parent.alloc(1u8);
+
+        let child = Arena::new(64);
+        child.alloc(0xAABB_u16);
+        child.alloc(0x1122_3344_5566_7788_u64);
+        let child_len = child.used_bytes();
+
+        parent.extend_from(&child).unwrap();
+        let start = parent.used_bytes() - child_len;
+        assert!(start >= 1);
+        assert_eq!(
+            &parent.buffer[start..start + child_len],
+            &child.buffer[..child_len]
+        );
+
+        // The u64 kept its alignment in the parent.
+        let child_u64 = child.buffer.as_ptr() as usize + child_len - 8;
+        let parent_u64 = parent.buffer.as_ptr() as usize + parent.used_bytes() - 8;
+        assert_eq!(child_u64 % 8, 0);
+        assert_eq!(parent_u64 % 8, 0);
+
+        // Doesn't fit: nothing changes.
+        let used = parent.used_bytes();
+        let big = Arena::new(256);
+        big.alloc_bytes(250);
+        assert_eq!(parent.extend_from(&big), Err(Error::OutOfMemory));
+        assert_eq!(parent.used_bytes(), used);
+    }
+
+    #[test]
+    fn test_grow_to_keeps_data() {
+        let mut arena = Arena::new(64);
+        for i in 1..=8u64 {
+            arena.alloc(i);
+        }
+        assert!(arena.alloc_err(9u64).is_err());
+        let used = arena.used_bytes();
+        let before = arena.buffer[..used].to_vec();
+        let old_base = arena.buffer.as_ptr() as usize;
+
+        arena.grow_to(256).unwrap();
+        assert_eq!(arena.capacity(), 256);
+        // The old bytes moved over, at the same alignment.
+        let shift = arena.used_bytes() - used;
+        assert_eq!(&arena.buffer[shift..shift + used], &before[..]);
+        assert_eq!(
+            (arena.buffer.as_ptr() as usize + shift) % MERGE_ALIGN,
+            old_base % MERGE_ALIGN
+        );
+
+        // Allocation continues past the old limit.
+        for i in 9..=20u64 {
+            assert_eq!(*arena.alloc(i), i);
+        }
+        assert!(arena.used_bytes() > 64);
+        assert_eq!(&arena.buffer[shift..shift + used], &before[..]);
+
+        // Shrinking is a no-op.
+        arena.grow_to(16).unwrap();
+        assert_eq!(arena.capacity(), 256);
+    }
+
+    #[test]
+    fn test_raw_handle_validates_until_reset() {
+        let mut arena = Arena::new(64);
+        let handle = arena.alloc_raw(Layout::new::<u64>());
+        arena.validate(&handle);
+        assert!(arena.contains(handle.as_ptr().as_ptr()));
+
+        arena.reset();
+        let fresh = arena.alloc_raw(Layout::new::<u64>());
+        arena.validate(&fresh);
+
+        #[cfg(debug_assertions)]
+        {
+            let stale =
+                std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| arena.validate(&handle)));
+            assert!(stale.is_err(), "use after reset went unnoticed");
+        }
+    }
+
+    #[test]
+    fn test_prewarm() {
+        let arena = Arena::new(8 * PREWARM_STRIDE + 100);
+        let first = arena.alloc(1u64);
+        arena.prewarm();
+        // Live allocations are left alone, and the rest is still usable.
+        assert_eq!(*first, 1);
+        for i in 0..1000u64 {
+            assert_eq!(*arena.alloc(i), i);
+        }
+    }
+
+    #[test]
+    fn test_alloc_err_out_of_memory() {
+        let arena = Arena::new(8);
+        assert!(arena.alloc_err(0u64).is_ok());
+        assert_eq!(arena.alloc_err(0u8).err(), Some(Error::OutOfMemory));
+    }
+
     #[test]
     #[should_panic(expected = "Arena OOM")]
     fn test_oom() {
@@ -0,0 +1,91 @@
+use super::Arena;
+use std::cell::RefCell;
+use std::ops::{Deref, DerefMut};
+
+/// A fixed set of pre-allocated arenas, handed out one at a time.
+///
+/// Typical for frame-based loops that want several scratch arenas per frame
+/// without reallocating their backing buffers. Like [`Arena`] itself, the
+/// pool is single-threaded.
+pub struct ArenaPool {
+    free: RefCell<Vec<Arena>>,
+}
+
+/// A guard for an arena borrowed from an [`ArenaPool`].
+///
+/// On drop, the arena is `reset` (running its deferred closures) and put
+/// back in the pool.
+pub struct PooledArena<'a> {
+    // In an `Option` so `Drop` can move it back into the pool.
+    arena: Option<Arena>,
+    pool: &'a ArenaPool,
+}
+
+impl ArenaPool {
+    /// Pre-allocates `count` arenas of `capacity_bytes` each.
+    pub fn new(count: usize, capacity_bytes: usize) -> Self {
+        Self {
+            free: RefCell::new((0..count).map(|_| Arena::new(capacity_bytes)).collect()),
+        }
+    }
+
+    /// Takes an arena from the pool, or returns `None` if all are in use.
+    pub fn acquire(&self) -> Option<PooledArena<'_>> {
+        let arena = self.free.borrow_mut().pop()?;
+        Some(PooledArena {
+            arena: Some(arena),
+            pool: self,
+        })
+    }
+
+    /// Returns the number of arenas currently available.
+    pub fn available(&self) -> usize {
+        self.free.borrow().len()
+    }
+}
+
+impl Deref for PooledArena<'_> {
+    type Target = Arena;
+    fn deref(&self) -> &Arena {
+        self.arena.as_ref().unwrap()
+    }
+}
+
+impl DerefMut for PooledArena<'_> {
+    fn deref_mut(&mut self) -> &mut Arena {
+        self.arena.as_mut().unwrap()
+    }
+}
+
+impl Drop for PooledArena<'_> {
+    fn drop(&mut self) {
+        if let Some(mut arena) = self.arena.take() {
+            arena.reset();
+            self.pool.free.borrow_mut().push(arena);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arena_comes_back_reset() {
+        let pool = ArenaPool::new(1, 256);
+        assert_eq!(pool.available(), 1);
+
+        {
+            let arena = pool.acquire().unwrap();
+            arena.alloc(42u64);
+            arena.alloc_bytes(32);
+            assert!(arena.used_bytes() >= 40);
+            assert!(pool.acquire().is_none());
+        }
+
+        assert_eq!(pool.available(), 1);
+        let arena = pool.acquire().unwrap();
+        assert_eq!(arena.used_bytes(), 0);
+        assert_eq!(arena.capacity(), 256);
+    }
+}
@@ -0,0 +1,92 @@
+use super::Arena;
+use crate::error::Error;
+use serde::{Deserialize, Serialize};
+use std::alloc::Layout;
+use std::slice;
+
+/// A serializable copy of an arena-stored slice, for caching an expensive
+/// table (e.g. a lookup table) to disk and loading it into a fresh arena.
+///
+/// The elements go through `T`'s own `Serialize`/`Deserialize`, so any serde
+/// format works and a snapshot only ever restores valid `T`s.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ArenaSnapshot<T> {
+    items: Vec<T>,
+}
+
+impl<T: Clone> ArenaSnapshot<T> {
+    /// Captures a copy of `items` (typically a slice living in an arena).
+    pub fn capture(items: &[T]) -> Self {
+        Self {
+            items: items.to_vec(),
+        }
+    }
+}
+
+impl<T> ArenaSnapshot<T> {
+    /// Returns the number of elements in the snapshot.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns true if the snapshot has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Allocates a slice in `arena` and fills it from the snapshot.
+    ///
+    /// `T: Copy` because the arena never runs destructors. Fails with
+    /// [`Error::OutOfMemory`] (leaving the arena untouched) if the slice
+    /// doesn't fit.
+    #[allow(clippy::mut_from_ref)]
+    pub fn restore_slice<'a>(&self, arena: &'a Arena) -> Result<&'a mut [T], Error>
+    where
+        T: Copy,
+    {
+        let layout = Layout::array::<T>(self.items.len()).map_err(|_| Error::OutOfMemory)?;
+        let ptr = arena
+            .try_alloc_layout(layout)
+            .ok_or(Error::OutOfMemory)?
+            .cast::<T>();
+        // SAFETY: `try_alloc_layout` returned a fresh region sized and aligned
+        // for `len` `T`s that nothing else references, and we initialize all
+        // of it before handing it out.
+        unsafe {
+            ptr.as_ptr()
+                .copy_from_nonoverlapping(self.items.as_ptr(), self.items.len());
+            Ok(slice::from_raw_parts_mut(ptr.as_ptr(), self.items.len()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_through_json() {
+        let arena = Arena::new(1024);
+        let table = arena.alloc([0u32; 64]);
+        for (i, v) in table.iter_mut().enumerate() {
+            *v = (i as u32).wrapping_mul(2_654_435_761);
+        }
+
+        let json = serde_json::to_string(&ArenaSnapshot::capture(&table[..])).unwrap();
+        let snapshot: ArenaSnapshot<u32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(snapshot.len(), 64);
+
+        let fresh = Arena::new(256);
+        let restored = snapshot.restore_slice(&fresh).unwrap();
+        assert_eq!(restored, &table[..]);
+        assert!(fresh.contains(restored.as_ptr()));
+
+        // Too big for the arena: nothing is allocated.
+        let tiny = Arena::new(16);
+        assert_eq!(
+            snapshot.restore_slice(&tiny).err(),
+            Some(Error::OutOfMemory)
+        );
+        assert_eq!(tiny.used_bytes(), 0);
+    }
+}
@@ -0,0 +1,79 @@
+#![doc = include_str!("README.md")]
+
+use crate::affinity;
+use crate::channel::channel;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Measures the average cross-thread round-trip latency on this machine.
+///
+/// Two threads are spawned and (best effort) pinned to two different cores.
+/// A token is bounced back and forth over a pair of SPSC channels
+/// `iterations` times, and the average round trip is returned.
+///
+/// Returns `Duration::ZERO` if `iterations` is 0.
+///
+/// This is a quick sanity check, not a rigorous benchmark: it includes the
+/// channel's blocking slow path whenever a thread has to wait.
+pub fn ping_pong_latency(iterations: usize) -> Duration {
+    if iterations == 0 {
+        return Duration::ZERO;
+    }
+
+    // Pick two distinct cores if we can. Pinning is best effort: on macOS
+    // (or inside restricted containers) it simply fails and we carry on.
+    let cores = affinity::get_core_ids();
+    let ping_core = cores.first().copied();
+    let pong_core = cores.get(1).copied().or(ping_core);
+
+    let (ping_tx, ping_rx) = channel::<usize>(1);
+    let (pong_tx, pong_rx) = channel::<usize>(1);
+
+    // The "pong" side echoes every token back until the ping side hangs up.
+    let echo = thread::spawn(move || {
+        if let Some(core) = pong_core {
+            let _ = affinity::pin_to_core(core);
+        }
+        while let Some(token) = ping_rx.recv() {
+            pong_tx.send(token);
+        }
+    });
+
+    // The "ping" side runs on its own thread so we never pin the caller.
+    let pinger = thread::spawn(move || {
+        if let Some(core) = ping_core {
+            let _ = affinity::pin_to_core(core);
+        }
+        let start = Instant::now();
+        for i in 0..iterations {
+            ping_tx.send(i);
+            let echoed = pong_rx.recv().expect("echo thread exited early");
+            debug_assert_eq!(echoed, i);
+        }
+        start.elapsed()
+        // `ping_tx` drops here, which stops the echo thread.
+    });
+
+    let elapsed = pinger.join().expect("ping thread panicked");
+    echo.join().expect("echo thread panicked");
+
+    elapsed.div_f64(iterations as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ping_pong_latency() {
+        let latency = ping_pong_latency(1_000);
+        println!("Average round trip: {:?}", latency);
+        assert!(latency > Duration::ZERO);
+        assert!(latency < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_zero_iterations() {
+        assert_eq!(ping_pong_latency(0), Duration::ZERO);
+    }
+}
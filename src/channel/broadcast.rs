@@ -0,0 +1,150 @@
+use super::{Receiver, Sender, channel};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// What a [`BroadcastSender`] does when one consumer's buffer is full.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Overflow {
+    /// Drop the message for that consumer only, and count it.
+    #[default]
+    Drop,
+    /// Block until that consumer has room. A slow consumer stalls the sender
+    /// (and therefore everyone else), but nobody misses a message.
+    Block,
+}
+
+/// The sending half of a broadcast channel. See [`broadcast`].
+pub struct BroadcastSender<T> {
+    senders: Vec<Sender<T>>,
+    /// Per-consumer drop counters, indexed like the `Vec<Receiver>`.
+    dropped: Vec<AtomicU64>,
+    overflow: Overflow,
+}
+
+/// Creates a fan-out channel: every consumer receives every message.
+///
+/// Each consumer gets its own SPSC channel of `capacity`, and `send` clones
+/// the item into each one. With the default [`Overflow::Drop`], a slow
+/// consumer only loses messages for itself; the others are unaffected.
+///
+/// ```
+/// use llt_rs::channel::broadcast;
+///
+/// let (tx, rxs) = broadcast::<u32>(8, 2);
+/// tx.send(7);
+/// for rx in &rxs {
+///     assert_eq!(rx.recv(), Some(7));
+/// }
+/// ```
+pub fn broadcast<T: Clone + Send>(
+    capacity: usize,
+    num_consumers: usize,
+) -> (BroadcastSender<T>, Vec<Receiver<T>>) {
+    broadcast_with_overflow(capacity, num_consumers, Overflow::Drop)
+}
+
+/// Like [`broadcast`], with an explicit [`Overflow`] policy.
+pub fn broadcast_with_overflow<T: Clone + Send>(
+    capacity: usize,
+    num_consumers: usize,
+    overflow: Overflow,
+) -> (BroadcastSender<T>, Vec<Receiver<T>>) {
+    let (senders, receivers) = (0..num_consumers).map(|_| channel(capacity)).unzip();
+    let dropped = (0..num_consumers).map(|_| AtomicU64::new(0)).collect();
+
+    (
+        BroadcastSender {
+            senders,
+            dropped,
+            overflow,
+        },
+        receivers,
+    )
+}
+
+impl<T: Clone> BroadcastSender<T> {
+    /// Sends a clone of `item` to every consumer.
+    ///
+    /// Returns how many consumers received it. Consumers whose `Receiver`
+    /// has been dropped are skipped.
+    pub fn send(&self, item: T) -> usize {
+        let mut delivered = 0;
+        for (tx, dropped) in self.senders.iter().zip(&self.dropped) {
+            // Only we and the Receiver hold the Arc.
+            if Arc::strong_count(&tx.shared) == 1 {
+                continue;
+            }
+            match self.overflow {
+                Overflow::Drop => {
+                    if tx.try_send(item.clone()).is_ok() {
+                        delivered += 1;
+                    } else {
+                        dropped.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+                Overflow::Block => {
+                    tx.send(item.clone());
+                    delivered += 1;
+                }
+            }
+        }
+        delivered
+    }
+}
+
+impl<T> BroadcastSender<T> {
+    /// Returns the number of consumers this sender was created with.
+    pub fn num_consumers(&self) -> usize {
+        self.senders.len()
+    }
+
+    /// Returns how many messages consumer `index` has missed because its
+    /// buffer was full. Always 0 under [`Overflow::Block`].
+    ///
+    /// # Panics
+    /// Panics if `index >= num_consumers()`.
+    pub fn dropped(&self, index: usize) -> u64 {
+        self.dropped[index].load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_every_consumer_sees_every_message() {
+        let (tx, rxs) = broadcast(16, 2);
+
+        let handles: Vec<_> = rxs
+            .into_iter()
+            .map(|rx| thread::spawn(move || std::iter::from_fn(|| rx.recv()).collect::<Vec<u32>>()))
+            .collect();
+
+        for i in 0..10 {
+            assert_eq!(tx.send(i), 2);
+        }
+        drop(tx);
+
+        for h in handles {
+            assert_eq!(h.join().unwrap(), (0..10).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn test_slow_consumer_drops_only_for_itself() {
+        let (tx, rxs) = broadcast(2, 2);
+
+        // Consumer 0 keeps up; consumer 1 never reads.
+        for i in 0..5 {
+            tx.send(i);
+            assert_eq!(rxs[0].try_recv(), Some(i));
+        }
+
+        assert_eq!(tx.dropped(0), 0);
+        assert_eq!(tx.dropped(1), 3);
+        assert_eq!(rxs[1].try_recv(), Some(0));
+        assert_eq!(rxs[1].try_recv(), Some(1));
+    }
+}
@@ -0,0 +1,132 @@
+use super::{Receiver, Sender, channel};
+use crate::error::Error;
+
+/// Merges several SPSC lanes into one consumer, serving them round-robin.
+///
+/// Each call resumes at the lane *after* the one that was served last, so a
+/// busy lane can't starve the others: any non-empty lane is served within
+/// one pass over the lanes.
+pub struct FanIn<T, M = ()> {
+    lanes: Vec<Receiver<T, M>>,
+    /// The lane to look at first on the next call.
+    next: usize,
+}
+
+/// Creates `lanes` SPSC channels of `capacity` each, and a [`FanIn`] that
+/// merges them. Hand one `Sender` to each producer thread.
+pub fn fan_in<T: Send>(lanes: usize, capacity: usize) -> (Vec<Sender<T>>, FanIn<T>) {
+    let (senders, receivers) = (0..lanes).map(|_| channel(capacity)).unzip();
+    (senders, FanIn::new(receivers))
+}
+
+impl<T, M> FanIn<T, M> {
+    /// Merges existing receivers. Lane `i` is `lanes[i]`.
+    pub fn new(lanes: Vec<Receiver<T, M>>) -> Self {
+        Self { lanes, next: 0 }
+    }
+
+    /// Takes one item from the next non-empty lane, or `None` if all are empty.
+    pub fn try_recv(&mut self) -> Option<T> {
+        self.try_recv_err().ok()
+    }
+
+    /// Like [`try_recv`](Self::try_recv), but returns an [`Error`].
+    ///
+    /// Fails with [`Error::Disconnected`] once every lane's sender is gone
+    /// and every lane is drained, or [`Error::Empty`] otherwise.
+    pub fn try_recv_err(&mut self) -> Result<T, Error> {
+        let n = self.lanes.len();
+        let mut all_disconnected = true;
+        for offset in 0..n {
+            let lane = (self.next + offset) % n;
+            match self.lanes[lane].try_recv_err() {
+                Ok(item) => {
+                    self.next = (lane + 1) % n;
+                    return Ok(item);
+                }
+                Err(Error::Disconnected) => {}
+                Err(_) => all_disconnected = false,
+            }
+        }
+        Err(if all_disconnected {
+            Error::Disconnected
+        } else {
+            Error::Empty
+        })
+    }
+
+    /// Returns the number of lanes.
+    pub fn num_lanes(&self) -> usize {
+        self.lanes.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_round_robin_order() {
+        let (senders, mut rx) = fan_in(3, 8);
+        for (lane, tx) in senders.iter().enumerate() {
+            for i in 0..3 {
+                tx.try_send(lane * 10 + i).unwrap();
+            }
+        }
+        let got: Vec<_> = std::iter::from_fn(|| rx.try_recv()).collect();
+        assert_eq!(got, vec![0, 10, 20, 1, 11, 21, 2, 12, 22]);
+
+        drop(senders);
+        assert_eq!(rx.try_recv_err(), Err(Error::Disconnected));
+    }
+
+    #[test]
+    fn test_saturated_lanes_share_fairly() {
+        const CAPACITY: usize = 64;
+        const WINDOW: usize = 3 * 1000;
+
+        let (senders, mut rx) = fan_in::<usize>(3, CAPACITY);
+        let producers: Vec<_> = senders
+            .into_iter()
+            .enumerate()
+            .map(|(lane, tx)| {
+                // Keep the lane full until the consumer goes away.
+                thread::spawn(move || {
+                    loop {
+                        match tx.try_send_err(lane) {
+                            Ok(()) => {}
+                            Err(Error::Full) => thread::yield_now(),
+                            Err(_) => return,
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        // Let every producer fill its lane before we start draining.
+        thread::sleep(Duration::from_millis(50));
+
+        let mut per_lane = [0usize; 3];
+        let mut received = 0;
+        while received < WINDOW {
+            match rx.try_recv() {
+                Some(lane) => {
+                    per_lane[lane] += 1;
+                    received += 1;
+                }
+                None => thread::yield_now(),
+            }
+        }
+        drop(rx);
+        for p in producers {
+            p.join().unwrap();
+        }
+
+        // A fair share is a third; allow scheduling noise, but no starvation.
+        for (lane, &count) in per_lane.iter().enumerate() {
+            assert!(count >= WINDOW / 5, "lane {} starved: {:?}", lane, per_lane);
+        }
+    }
+}
@@ -1,59 +1,567 @@
 #![doc = include_str!("README.md")]
 
+mod broadcast;
+mod fan_in;
+
+pub use broadcast::{BroadcastSender, Overflow, broadcast, broadcast_with_overflow};
+pub use fan_in::{FanIn, fan_in};
+
+use crate::error::Error;
 use crate::ring_buffer::RingBuffer;
-use std::sync::{Arc, Condvar, Mutex};
+use std::fmt;
+use std::hint;
+use std::mem;
+use std::sync::atomic::{self, AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, OnceLock, Weak};
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+/// Source of [`ChannelId`]s.
+static NEXT_CHANNEL_ID: AtomicU64 = AtomicU64::new(0);
+
+/// A process-unique identifier for a channel, shared by its Sender and Receiver.
+///
+/// Useful for correlating producer-side and consumer-side logs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ChannelId(u64);
+
+impl ChannelId {
+    /// Returns the raw id.
+    pub fn as_u64(self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for ChannelId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "channel#{}", self.0)
+    }
+}
+
 /// The shared state between the Sender and Receiver.
-struct Shared<T> {
+struct Shared<T, M> {
+    id: ChannelId,
     buffer: RingBuffer<T>,
     signal: Condvar,
-    // The Mutex is required by Condvar. We use a () as a "dummy"
-    // payload because the data itself is protected by the RingBuffer's atomics.
-    lock: Mutex<()>,
+    // The Mutex is required by Condvar. The data itself is protected by the
+    // RingBuffer's atomics, so the payload is free for user metadata
+    // (`()` by default).
+    lock: Mutex<M>,
+    /// Number of threads parked (or about to park) on `signal`.
+    /// Lets the fast path skip the lock entirely when nobody is asleep.
+    sleepers: AtomicUsize,
+    /// Set when the `Sender` is dropped or explicitly closed.
+    /// We can't rely on `Arc::strong_count` alone: the Sender's `Arc` is only
+    /// released *after* its `Drop::drop` has sent the wake-up.
+    disconnected: AtomicBool,
+    /// Why the Sender closed, if it said (`Sender::close_with_reason`).
+    /// Set before `disconnected`, so a receiver that sees the flag sees it.
+    close_reason: OnceLock<String>,
+    /// How many times the slow path retries (spinning) before parking.
+    spin_iters: usize,
+    /// If false, an empty `recv` busy-polls instead of parking on the condvar.
+    park_on_empty: bool,
+    /// Set by `ChannelBuilder::coalescing`: the consumer reads the *last*
+    /// queued item under `lock`, since `try_send_or_replace` may overwrite it.
+    coalescing: bool,
+    /// Occupancy alarm configured by `Sender::set_high_water`.
+    high_water: HighWater,
+    /// Waker registered by a `Pending` `Receiver::poll_recv`.
+    rx_waker: Mutex<Option<Waker>>,
+    /// True while `rx_waker` holds a waker, so `wake` can skip its lock.
+    rx_waker_set: AtomicBool,
+    /// Sends refused because the channel was full.
+    #[cfg(feature = "stats")]
+    rejected: AtomicU64,
+}
+
+/// A snapshot of a channel's counters, returned by [`Sender::metrics`] and
+/// [`Receiver::metrics`].
+///
+/// The fields are read together (a couple of loads, no locks), so they are
+/// consistent with each other up to concurrent sends and receives:
+/// `occupancy == sent - received` always holds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChannelMetrics {
+    /// Items queued at the time of the snapshot.
+    pub occupancy: usize,
+    /// The channel's capacity (after rounding up to a power of two).
+    pub capacity: usize,
+    /// Total items ever sent (wraps at `usize::MAX`).
+    pub sent: u64,
+    /// Total items ever received (wraps at `usize::MAX`).
+    pub received: u64,
+    /// Items that `try_send`, `try_send_err`, `try_send_all` or an expired
+    /// `send_timeout` refused because the channel was full. Each refusal
+    /// counts, so an item the caller retries and later delivers is still
+    /// counted here (and in `sent`).
+    #[cfg(feature = "stats")]
+    pub rejected: u64,
+}
+
+/// A one-shot callback fired when occupancy rises past a threshold.
+struct HighWater {
+    /// `usize::MAX` means "not configured", so the check is one Relaxed load.
+    threshold: AtomicUsize,
+    /// True while the callback may fire; cleared when it fires and set again
+    /// once a send observes occupancy back at or below the threshold.
+    armed: AtomicBool,
+    callback: Mutex<Option<Box<dyn Fn() + Send + Sync>>>,
+}
+
+impl HighWater {
+    fn new() -> Self {
+        Self {
+            threshold: AtomicUsize::new(usize::MAX),
+            armed: AtomicBool::new(true),
+            callback: Mutex::new(None),
+        }
+    }
+
+    /// Checks the occupancy after a send, firing the callback on an upward crossing.
+    /// `occupied` is only called when an alarm is configured.
+    fn check(&self, occupied: impl FnOnce() -> usize) {
+        let threshold = self.threshold.load(Ordering::Relaxed);
+        if threshold == usize::MAX {
+            return;
+        }
+        if occupied() > threshold {
+            if self.armed.swap(false, Ordering::Relaxed)
+                && let Some(cb) = &*self.callback.lock().unwrap()
+            {
+                cb();
+            }
+        } else if !self.armed.load(Ordering::Relaxed) {
+            self.armed.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+/// What `Shared::take_if` found at the front of the buffer.
+enum Front<T> {
+    Taken(T),
+    Rejected,
+    Empty,
+}
+
+impl<T, M> Shared<T, M> {
+    fn metrics(&self) -> ChannelMetrics {
+        let (sent, received) = self.buffer.counters();
+        ChannelMetrics {
+            occupancy: sent.wrapping_sub(received),
+            capacity: self.buffer.capacity(),
+            sent: sent as u64,
+            received: received as u64,
+            #[cfg(feature = "stats")]
+            rejected: self.rejected.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Counts `n` items a full channel refused (`stats` only).
+    fn record_rejected(&self, n: usize) {
+        #[cfg(feature = "stats")]
+        self.rejected.fetch_add(n as u64, Ordering::Relaxed);
+        #[cfg(not(feature = "stats"))]
+        let _ = n;
+    }
+
+    /// Takes one item off the buffer without blocking. Every consumer path
+    /// that doesn't already hold `lock` must go through here.
+    fn take(&self) -> Option<T> {
+        // `head` only grows, so if two or more items look queued, the one at
+        // `tail` can't be the last one: no need to lock.
+        if self.coalescing && self.buffer.len() <= 1 {
+            let _guard = self.lock.lock().unwrap();
+            return self.buffer.recv();
+        }
+        self.buffer.recv()
+    }
+
+    /// Like `take`, but only takes the front item if `pred` accepts it.
+    /// The caller must be the sole consumer and hold `&mut Receiver`, so
+    /// `pred` can't reach the receiver.
+    fn take_if(&self, pred: impl FnOnce(&T) -> bool) -> Front<T> {
+        // As in `take`: the producer may be overwriting the last item.
+        let _guard = (self.coalescing && self.buffer.len() <= 1).then(|| self.lock.lock().unwrap());
+        // SAFETY: We are the consumer and nothing receives until `front` is
+        // no longer used (`pred` can't reach the receiver, see above).
+        match unsafe { self.buffer.peek() } {
+            None => return Front::Empty,
+            Some(front) if !pred(front) => return Front::Rejected,
+            Some(_) => {}
+        }
+        match self.buffer.recv() {
+            Some(item) => Front::Taken(item),
+            None => unreachable!("only the consumer removes items"),
+        }
+    }
+
+    /// Wakes any thread parked on the condvar.
+    ///
+    /// Called after every successful buffer operation. The SeqCst fence pairs
+    /// with the one in `sleep`: either we see the sleeper's increment, or
+    /// the sleeper sees our buffer update before it goes to sleep.
+    fn wake(&self) {
+        atomic::fence(Ordering::SeqCst);
+        if self.sleepers.load(Ordering::Relaxed) > 0 {
+            // Taking the lock guarantees the sleeper is inside `wait`
+            // (and not between its re-check and `wait`), so the notify is never lost.
+            let _guard = self.lock.lock().unwrap();
+            self.signal.notify_all();
+        }
+        // Same handshake for an async receiver (see `register_waker`).
+        if self.rx_waker_set.load(Ordering::Relaxed) {
+            // Clear the flag under the lock, so it can't hide a waker
+            // registered right after our `take`.
+            let waker = {
+                let mut slot = self.rx_waker.lock().unwrap();
+                self.rx_waker_set.store(false, Ordering::Relaxed);
+                slot.take()
+            };
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        }
+    }
+
+    /// Stores the receiver's waker. The caller must re-check the buffer
+    /// afterwards: the SeqCst fence pairs with the one in `wake`.
+    fn register_waker(&self, waker: &Waker) {
+        let mut slot = self.rx_waker.lock().unwrap();
+        match &mut *slot {
+            Some(old) if old.will_wake(waker) => {}
+            _ => *slot = Some(waker.clone()),
+        }
+        self.rx_waker_set.store(true, Ordering::Relaxed);
+        drop(slot);
+        atomic::fence(Ordering::SeqCst);
+    }
+
+    /// Registers the current thread as a sleeper.
+    /// Must be called while holding `lock`, *before* the final re-check.
+    fn announce_sleep(&self) {
+        self.sleepers.fetch_add(1, Ordering::SeqCst);
+        atomic::fence(Ordering::SeqCst);
+    }
+
+    /// Undoes `announce_sleep` once the thread is done waiting.
+    fn retire_sleep(&self) {
+        self.sleepers.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    /// Blocks on the condvar. The caller must have called `announce_sleep`.
+    fn sleep<'a>(&self, guard: MutexGuard<'a, M>) -> MutexGuard<'a, M> {
+        self.signal.wait(guard).unwrap()
+    }
+
+    /// Like `sleep`, but gives up after `timeout`.
+    fn sleep_timeout<'a>(&self, guard: MutexGuard<'a, M>, timeout: Duration) -> MutexGuard<'a, M> {
+        self.signal.wait_timeout(guard, timeout).unwrap().0
+    }
+}
+
+/// The error returned by [`Receiver::recv_timeout`] and [`Receiver::recv_deadline`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecvTimeoutError {
+    /// No item arrived before the deadline.
+    Timeout,
+    /// The Sender is gone and the channel is drained.
+    Disconnected,
+}
+
+impl fmt::Display for RecvTimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecvTimeoutError::Timeout => write!(f, "timed out waiting on channel"),
+            RecvTimeoutError::Disconnected => write!(f, "channel is empty and disconnected"),
+        }
+    }
 }
 
+impl std::error::Error for RecvTimeoutError {}
+
 /// The sending half of the SPSC channel.
-pub struct Sender<T> {
-    shared: Arc<Shared<T>>,
+///
+/// `M` is optional shared metadata (see [`ChannelBuilder::build_with_metadata`]).
+pub struct Sender<T, M = ()> {
+    shared: Arc<Shared<T, M>>,
+    /// Items this Sender got into the channel (see `sent_count`).
+    sent: AtomicU64,
 }
 
 /// The receiving half of the SPSC channel.
-pub struct Receiver<T> {
-    shared: Arc<Shared<T>>,
+///
+/// `M` is optional shared metadata (see [`ChannelBuilder::build_with_metadata`]).
+pub struct Receiver<T, M = ()> {
+    shared: Arc<Shared<T, M>>,
+}
+
+/// Wakes a channel's parked threads so they re-check external conditions,
+/// e.g. the flag passed to [`Receiver::recv_cancellable`].
+///
+/// Get one with [`Sender::notifier`] or [`Receiver::notifier`] and hand it
+/// to the shutdown code. It doesn't keep the channel alive (or count as a
+/// Sender for disconnection); once both halves are gone, `notify` does nothing.
+pub struct Notifier<T, M = ()> {
+    shared: Weak<Shared<T, M>>,
+}
+
+impl<T, M> Clone for Notifier<T, M> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T, M> Notifier<T, M> {
+    /// Wakes every thread parked on the channel. Set your flag *before*
+    /// calling this, or the woken threads may miss it and park again.
+    pub fn notify(&self) {
+        if let Some(shared) = self.shared.upgrade() {
+            shared.wake();
+        }
+    }
 }
 
 /// Creates a new SPSC channel with the given capacity.
 ///
 /// Capacity will be rounded up to the next power of 2.
+/// This is shorthand for `ChannelBuilder::new().capacity(capacity).build()`.
 pub fn channel<T: Send>(capacity: usize) -> (Sender<T>, Receiver<T>) {
-    let shared = Arc::new(Shared {
-        buffer: RingBuffer::new(capacity),
-        signal: Condvar::new(),
-        lock: Mutex::new(()),
-    });
+    ChannelBuilder::new().capacity(capacity).build()
+}
+
+/// Wraps an existing [`RingBuffer`] in a channel, without reallocating it.
+///
+/// Items already queued in `buffer` are received first. This is shorthand
+/// for `ChannelBuilder::new().build_from_buffer(buffer)`.
+pub fn channel_from_buffer<T: Send>(buffer: RingBuffer<T>) -> (Sender<T>, Receiver<T>) {
+    ChannelBuilder::new().build_from_buffer(buffer)
+}
+
+/// The default capacity used by [`ChannelBuilder`].
+pub const DEFAULT_CAPACITY: usize = 1024;
+
+/// Configures and creates an SPSC channel.
+///
+/// Use this when you want to tune the latency/CPU trade-off of the
+/// blocking slow path.
+///
+/// ```
+/// use llt_rs::channel::ChannelBuilder;
+///
+/// let (tx, rx) = ChannelBuilder::new()
+///     .capacity(256)
+///     .spin_iters(100)
+///     .build::<u64>();
+/// tx.send(1);
+/// assert_eq!(rx.recv(), Some(1));
+/// ```
+#[derive(Clone, Debug)]
+pub struct ChannelBuilder {
+    capacity: usize,
+    spin_iters: usize,
+    park_on_empty: bool,
+    coalescing: bool,
+}
+
+impl Default for ChannelBuilder {
+    fn default() -> Self {
+        Self {
+            capacity: DEFAULT_CAPACITY,
+            spin_iters: 0,
+            park_on_empty: true,
+            coalescing: false,
+        }
+    }
+}
+
+impl ChannelBuilder {
+    /// Creates a builder with the default settings:
+    /// capacity of `DEFAULT_CAPACITY`, no spinning, park when empty.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the channel capacity. It will be rounded up to the next power of 2.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Sets how many times a blocking `send`/`recv` retries (with a
+    /// `spin_loop` hint) before parking the thread.
+    ///
+    /// Higher values lower wake-up latency at the cost of CPU.
+    pub fn spin_iters(mut self, spin_iters: usize) -> Self {
+        self.spin_iters = spin_iters;
+        self
+    }
+
+    /// Controls whether a blocking `recv` on an empty channel parks the thread.
+    ///
+    /// If `false`, the receiver busy-polls forever (burning 100% of a core)
+    /// for the lowest possible wake-up latency.
+    pub fn park_on_empty(mut self, park_on_empty: bool) -> Self {
+        self.park_on_empty = park_on_empty;
+        self
+    }
+
+    /// Enables [`Sender::try_send_or_replace`].
+    ///
+    /// The receiver then takes the channel's lock whenever it reads the last
+    /// queued item (the one the sender may be overwriting), so leave this off
+    /// unless you need coalescing.
+    pub fn coalescing(mut self, coalescing: bool) -> Self {
+        self.coalescing = coalescing;
+        self
+    }
+
+    /// Creates the channel.
+    pub fn build<T: Send>(self) -> (Sender<T>, Receiver<T>) {
+        self.build_with_metadata()
+    }
+
+    /// Creates a channel that also carries a shared metadata value of type `M`.
+    ///
+    /// The metadata lives inside the Mutex the channel already needs for its
+    /// Condvar, so it costs no extra allocation. Both halves can read and
+    /// update it with `with_metadata` (e.g. a generation counter or a
+    /// shutdown reason).
+    pub fn build_with_metadata<T: Send, M: Default>(self) -> (Sender<T, M>, Receiver<T, M>) {
+        let buffer = RingBuffer::new(self.capacity);
+        self.wrap(buffer)
+    }
+
+    /// Creates the channel around an existing `buffer` instead of allocating
+    /// one. The builder's `capacity` is ignored; the buffer's is used.
+    ///
+    /// Items already queued in `buffer` are received first.
+    pub fn build_from_buffer<T: Send>(self, buffer: RingBuffer<T>) -> (Sender<T>, Receiver<T>) {
+        self.wrap(buffer)
+    }
 
-    (
-        Sender {
-            shared: shared.clone(),
-        },
-        Receiver { shared },
-    )
+    fn wrap<T: Send, M: Default>(self, buffer: RingBuffer<T>) -> (Sender<T, M>, Receiver<T, M>) {
+        let shared = Arc::new(Shared {
+            id: ChannelId(NEXT_CHANNEL_ID.fetch_add(1, Ordering::Relaxed)),
+            buffer,
+            signal: Condvar::new(),
+            lock: Mutex::new(M::default()),
+            sleepers: AtomicUsize::new(0),
+            disconnected: AtomicBool::new(false),
+            close_reason: OnceLock::new(),
+            spin_iters: self.spin_iters,
+            park_on_empty: self.park_on_empty,
+            coalescing: self.coalescing,
+            high_water: HighWater::new(),
+            rx_waker: Mutex::new(None),
+            rx_waker_set: AtomicBool::new(false),
+            #[cfg(feature = "stats")]
+            rejected: AtomicU64::new(0),
+        });
+
+        (
+            Sender {
+                shared: shared.clone(),
+                sent: AtomicU64::new(0),
+            },
+            Receiver { shared },
+        )
+    }
 }
 
 // --- Sender Implementation ---
 
-impl<T> Sender<T> {
+impl<T, M> Sender<T, M> {
     /// Attempts to send an item immediately without blocking.
     ///
     /// If the channel is full, this returns `Err(item)`.
     pub fn try_send(&self, item: T) -> Result<(), T> {
-        match self.shared.buffer.send(item) {
-            Ok(_) => {
-                // Wake up the receiver, in case it's sleeping.
-                self.shared.signal.notify_one();
-                Ok(())
+        self.send_now(item)
+            .inspect_err(|_| self.shared.record_rejected(1))
+    }
+
+    /// `try_send` without counting a refusal, for callers that still
+    /// deliver the item another way.
+    fn send_now(&self, item: T) -> Result<(), T> {
+        self.shared.buffer.send(item)?;
+        // Wake up the receiver, in case it's sleeping.
+        self.on_sent(1);
+        Ok(())
+    }
+
+    /// Sends `item`, or, if the channel is full, overwrites the most recently
+    /// queued item with it and returns the overwritten one.
+    ///
+    /// For "latest value wins" channels (e.g. sensor readings): the queue
+    /// stays bounded, and when saturated its newest entry is always the
+    /// newest update. Returns `None` if `item` was queued normally.
+    ///
+    /// # Panics
+    /// Panics unless the channel was built with [`ChannelBuilder::coalescing`].
+    pub fn try_send_or_replace(&self, item: T) -> Option<T> {
+        assert!(
+            self.shared.coalescing,
+            "try_send_or_replace requires ChannelBuilder::coalescing(true)"
+        );
+        let item = match self.send_now(item) {
+            Ok(()) => return None,
+            Err(item) => item,
+        };
+
+        let item = {
+            let _guard = self.shared.lock.lock().unwrap();
+            // SAFETY: With `coalescing`, the receiver only reads the last
+            // queued item while holding `lock` (see `Shared::take`), and we
+            // hold it, so it can't take the slot while we write to it.
+            match unsafe { self.shared.buffer.peek_last_unconsumed_mut() } {
+                Some(last) => return Some(mem::replace(last, item)),
+                None => item,
+            }
+        };
+        // The receiver drained the channel in the meantime, so there is room.
+        if self.send_now(item).is_err() {
+            unreachable!("only the sender can fill the channel");
+        }
+        None
+    }
+
+    /// Pushes items from the front of `items` until the channel is full.
+    ///
+    /// Returns the items that did not fit, in their original order, so the
+    /// caller can retry them later. An empty result means everything was sent.
+    pub fn try_send_all(&self, items: Vec<T>) -> Vec<T> {
+        let mut items = items.into_iter();
+        let mut sent = 0;
+        let mut leftover = Vec::new();
+
+        for item in items.by_ref() {
+            if let Err(item) = self.shared.buffer.send(item) {
+                leftover.push(item);
+                break;
             }
-            Err(item) => Err(item),
+            sent += 1;
+        }
+        leftover.extend(items);
+        self.shared.record_rejected(leftover.len());
+
+        // One wakeup for the whole batch.
+        if sent > 0 {
+            self.on_sent(sent);
+        }
+        leftover
+    }
+
+    /// Like [`try_send`](Self::try_send), but returns an [`Error`].
+    ///
+    /// Fails with [`Error::Disconnected`] if the Receiver is gone, or
+    /// [`Error::Full`] (dropping the item) if there is no room.
+    pub fn try_send_err(&self, item: T) -> Result<(), Error> {
+        // We and the Receiver are the only Arc owners.
+        if Arc::strong_count(&self.shared) == 1 {
+            return Err(Error::Disconnected);
         }
+        self.try_send(item).map_err(|_| Error::Full)
     }
 
     /// Sends an item, blocking the current thread if the channel is full.
@@ -62,7 +570,7 @@ impl<T> Sender<T> {
         match self.shared.buffer.send(item) {
             Ok(_) => {
                 // Success! Notify the receiver and return.
-                self.shared.signal.notify_one();
+                self.on_sent(1);
                 return;
             }
             Err(returned_item) => {
@@ -71,14 +579,29 @@ impl<T> Sender<T> {
             }
         }
 
-        // 2. Slow Path: The buffer is full. We must wait.
+        // 2. Spin Path: Retry for a while before paying for a park.
+        for _ in 0..self.shared.spin_iters {
+            hint::spin_loop();
+            match self.shared.buffer.send(item) {
+                Ok(_) => {
+                    self.on_sent(1);
+                    return;
+                }
+                Err(returned_item) => item = returned_item,
+            }
+        }
+
+        // 3. Slow Path: The buffer is full. We must wait.
         let mut guard = self.shared.lock.lock().unwrap();
+        self.shared.announce_sleep();
         loop {
             // Try again inside the lock (in case another thread
             // woke us up but we were too slow).
             match self.shared.buffer.send(item) {
                 Ok(_) => {
-                    self.shared.signal.notify_one();
+                    self.shared.retire_sleep();
+                    drop(guard);
+                    self.on_sent(1);
                     return;
                 }
                 Err(returned_item) => {
@@ -86,103 +609,679 @@ impl<T> Sender<T> {
                     // Still full. Go to sleep.
                     // `wait` atomically releases the lock and blocks.
                     // When it wakes up, it re-acquires the lock.
-                    guard = self.shared.signal.wait(guard).unwrap();
+                    guard = self.shared.sleep(guard);
                 }
             }
         }
     }
+
+    /// Sends an item, blocking for at most `timeout` while the channel is full.
+    ///
+    /// Returns `Err(item)` if there was still no room when the time ran out.
+    pub fn send_timeout(&self, item: T, timeout: Duration) -> Result<(), T> {
+        match Instant::now().checked_add(timeout) {
+            Some(deadline) => self.send_deadline(item, deadline),
+            // A timeout too large to represent is effectively "forever".
+            None => {
+                self.send(item);
+                Ok(())
+            }
+        }
+    }
+
+    /// Sends an item, blocking until the absolute `deadline` at the latest.
+    ///
+    /// Returns `Err(item)` if the channel was still full at the deadline.
+    pub fn send_deadline(&self, mut item: T, deadline: Instant) -> Result<(), T> {
+        // 1. Fast Path: Try a lock-free send.
+        match self.shared.buffer.send(item) {
+            Ok(_) => {
+                self.on_sent(1);
+                return Ok(());
+            }
+            Err(returned_item) => item = returned_item,
+        }
+
+        // 2. Slow Path: Sleep until there is room or the deadline passes.
+        let mut guard = self.shared.lock.lock().unwrap();
+        self.shared.announce_sleep();
+        let result = loop {
+            match self.shared.buffer.send(item) {
+                Ok(_) => break Ok(()),
+                Err(returned_item) => item = returned_item,
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                break Err(item);
+            }
+            guard = self.shared.sleep_timeout(guard, deadline - now);
+        };
+        self.shared.retire_sleep();
+        drop(guard);
+
+        match result {
+            Ok(()) => self.on_sent(1),
+            Err(_) => self.shared.record_rejected(1),
+        }
+        result
+    }
+
+    /// Blocks until the receiver has drained every queued item.
+    ///
+    /// A producer-side barrier: e.g. send a shutdown sentinel, then wait
+    /// for the consumer to get through it before tearing down shared state.
+    /// Returns early if the receiver is dropped (nothing would drain it).
+    pub fn wait_empty(&self) {
+        if self.shared.buffer.is_empty() {
+            return;
+        }
+        let mut guard = self.shared.lock.lock().unwrap();
+        self.shared.announce_sleep();
+        // Every receive wakes sleepers, but a dropped Receiver doesn't, so
+        // re-check its presence periodically rather than parking for good.
+        while !self.shared.buffer.is_empty() && Arc::strong_count(&self.shared) > 1 {
+            guard = self.shared.sleep_timeout(guard, Duration::from_millis(10));
+        }
+        self.shared.retire_sleep();
+    }
+
+    /// Wakes the receiver and checks the high-water mark after `count`
+    /// items were successfully sent.
+    fn on_sent(&self, count: u64) {
+        // Only this Sender writes it, so a load/store pair is enough.
+        let sent = self.sent.load(Ordering::Relaxed);
+        self.sent.store(sent.wrapping_add(count), Ordering::Relaxed);
+        self.shared.wake();
+        self.shared.high_water.check(|| self.shared.buffer.len());
+    }
+
+    /// Calls `cb` the first time a send pushes occupancy above `threshold`.
+    ///
+    /// The alarm re-arms once a later send sees occupancy back at or below
+    /// `threshold`, so `cb` fires once per excursion rather than on every
+    /// send. It runs on the sending thread, so keep it cheap (e.g. flip a
+    /// flag that tells upstream to slow down). Calling this again replaces
+    /// the previous threshold and callback.
+    pub fn set_high_water(&self, threshold: usize, cb: impl Fn() + Send + Sync + 'static) {
+        let hw = &self.shared.high_water;
+        *hw.callback.lock().unwrap() = Some(Box::new(cb));
+        hw.armed.store(true, Ordering::Relaxed);
+        hw.threshold.store(threshold, Ordering::Relaxed);
+    }
+
+    /// Signals end-of-stream without dropping the `Sender`.
+    ///
+    /// Once the receiver has drained the remaining items, `recv` returns `None`,
+    /// exactly as if the Sender had been dropped. This is useful when you want
+    /// to keep the handle around (e.g. for metrics) after the stream is done.
+    ///
+    /// Items sent *after* `close` may never be observed by the receiver.
+    pub fn close(&self) {
+        self.shared.disconnected.store(true, Ordering::Release);
+        self.shared.wake();
+    }
+
+    /// Like [`close`](Self::close), but records why, e.g. the error that
+    /// stopped the producer. The receiver reads it with
+    /// [`Receiver::disconnect_reason`]. Only the first reason is kept.
+    pub fn close_with_reason(&self, reason: impl Into<String>) {
+        let _ = self.shared.close_reason.set(reason.into());
+        self.close();
+    }
+
+    /// Returns true if `close` has been called.
+    pub fn is_closed(&self) -> bool {
+        self.shared.disconnected.load(Ordering::Acquire)
+    }
+
+    /// Returns the id of this channel (the same as its Receiver's).
+    pub fn id(&self) -> ChannelId {
+        self.shared.id
+    }
+
+    /// Returns a [`Notifier`] for waking this channel's parked threads.
+    pub fn notifier(&self) -> Notifier<T, M> {
+        Notifier {
+            shared: Arc::downgrade(&self.shared),
+        }
+    }
+
+    /// Returns how many items this Sender has successfully sent.
+    ///
+    /// Unlike [`ChannelMetrics::sent`] this is per producer, e.g. to compare
+    /// the throughput of the lanes of a [`FanIn`]. Rejected items (a full
+    /// `try_send`, an expired `send_timeout`) don't count.
+    pub fn sent_count(&self) -> u64 {
+        self.sent.load(Ordering::Relaxed)
+    }
+
+    /// Returns a snapshot of the channel's counters in one call.
+    pub fn metrics(&self) -> ChannelMetrics {
+        self.shared.metrics()
+    }
+
+    /// Locks the shared metadata and runs `f` on it.
+    ///
+    /// This takes the same lock the blocking slow path uses, so keep `f` short.
+    pub fn with_metadata<R>(&self, f: impl FnOnce(&mut M) -> R) -> R {
+        f(&mut self.shared.lock.lock().unwrap())
+    }
 }
 
 // --- Receiver Implementation ---
 
-impl<T> Receiver<T> {
+impl<T, M> Receiver<T, M> {
     /// Attempts to receive an item immediately without blocking.
     ///
     /// If the channel is empty, this returns `None`.
     pub fn try_recv(&self) -> Option<T> {
-        match self.shared.buffer.recv() {
+        match self.shared.take() {
             Some(item) => {
                 // Notify the producer that space has opened up.
-                self.shared.signal.notify_one();
+                self.shared.wake();
                 Some(item)
             }
             None => None,
         }
     }
 
+    /// Like [`try_recv`](Self::try_recv), but returns an [`Error`].
+    ///
+    /// Fails with [`Error::Disconnected`] once the Sender is gone and the
+    /// channel is drained, or [`Error::Empty`] if nothing is queued yet.
+    pub fn try_recv_err(&self) -> Result<T, Error> {
+        if let Some(item) = self.try_recv() {
+            return Ok(item);
+        }
+        if self.is_disconnected() {
+            // The Sender may have pushed one last item before leaving.
+            return self.try_recv().ok_or(Error::Disconnected);
+        }
+        Err(Error::Empty)
+    }
+
     /// Receives an item, blocking the current thread if the channel is empty.
     ///
     /// Returns `None` if the `Sender` has been dropped.
     pub fn recv(&self) -> Option<T> {
         // 1. Fast Path: Try a lock-free receive.
-        if let Some(item) = self.shared.buffer.recv() {
-            self.shared.signal.notify_one();
+        if let Some(item) = self.shared.take() {
+            self.shared.wake();
             return Some(item);
         }
 
-        // 2. Slow Path: The buffer is empty. We must wait.
+        // 2. Spin Path: Retry for a while before paying for a park.
+        for _ in 0..self.shared.spin_iters {
+            hint::spin_loop();
+            if let Some(item) = self.shared.take() {
+                self.shared.wake();
+                return Some(item);
+            }
+        }
+
+        if !self.shared.park_on_empty {
+            return self.busy_recv();
+        }
+
+        // 3. Slow Path: The buffer is empty. We must wait.
         let mut guard = self.shared.lock.lock().unwrap();
+        self.shared.announce_sleep();
         loop {
             match self.shared.buffer.recv() {
                 Some(item) => {
-                    self.shared.signal.notify_one();
+                    self.shared.retire_sleep();
+                    drop(guard);
+                    self.shared.wake();
                     return Some(item);
                 }
                 None => {
-                    // Check for disconnection. If we are the *only*
-                    // Arc owner left, the Sender must be gone.
-                    if Arc::strong_count(&self.shared) == 1 {
-                        return None;
+                    if self.is_disconnected() {
+                        self.shared.retire_sleep();
+                        // The Sender may have pushed a final item between our
+                        // `recv` and the flag check, so drain once more.
+                        return self.shared.buffer.recv();
                     }
                     // Still empty. Wait for a signal.
-                    guard = self.shared.signal.wait(guard).unwrap();
+                    guard = self.shared.sleep(guard);
                 }
             }
         }
     }
 
-    // You could also add `recv_timeout` here as a further exercise!
-}
-
-impl<T> Drop for Sender<T> {
-    fn drop(&mut self) {
-        // When the sender drops, we must wake up any
-        // sleeping receiver so it can check for disconnection.
-        self.shared.signal.notify_one();
+    /// Polls the buffer until an item arrives or the Sender disconnects.
+    /// Used instead of parking when `park_on_empty` is disabled.
+    fn busy_recv(&self) -> Option<T> {
+        loop {
+            if let Some(item) = self.shared.take() {
+                self.shared.wake();
+                return Some(item);
+            }
+            if self.is_disconnected() {
+                return self.shared.take();
+            }
+            hint::spin_loop();
+        }
     }
-}
-
-// --- Tests ---
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::{thread, time::Duration};
-
-    #[test]
-    fn test_blocking_send_recv() {
-        let (tx, rx) = channel(1); // Capacity of 1
 
-        // Send one item, should be fine.
-        tx.send("hello");
+    /// Receives an item (blocking like `recv`) and maps it through `f`.
+    ///
+    /// Returns `None` if the `Sender` has been dropped and the channel is drained.
+    pub fn recv_map<R>(&self, f: impl FnOnce(T) -> R) -> Option<R> {
+        self.recv().map(f)
+    }
 
-        // Spawn a producer that will block
-        let tx_clone = tx.shared.clone(); // Use Arc for test
-        let _producer = thread::spawn(move || {
-            let sender = Sender { shared: tx_clone };
-            sender.send("world");
-            // This thread is now blocked
-        });
+    /// Blocks until the channel has at least one item, without taking it.
+    ///
+    /// Returns `true` once data is available, or `false` if the Sender is
+    /// gone and the channel is drained. Pair it with `try_recv_batch` to
+    /// separate "wait for work" from "grab the work".
+    pub fn wait_nonempty(&self) -> bool {
+        if !self.shared.buffer.is_empty() {
+            return true;
+        }
+        for _ in 0..self.shared.spin_iters {
+            hint::spin_loop();
+            if !self.shared.buffer.is_empty() {
+                return true;
+            }
+        }
 
-        // Wait a moment
-        thread::sleep(Duration::from_millis(50));
+        let mut guard = self.shared.lock.lock().unwrap();
+        self.shared.announce_sleep();
+        loop {
+            if !self.shared.buffer.is_empty() {
+                self.shared.retire_sleep();
+                return true;
+            }
+            if self.is_disconnected() {
+                self.shared.retire_sleep();
+                // A final item may have landed between the two checks.
+                return !self.shared.buffer.is_empty();
+            }
+            guard = self.shared.sleep(guard);
+        }
+    }
 
-        // Now, receive an item, which should unblock the producer
-        assert_eq!(rx.recv(), Some("hello"));
-        assert_eq!(rx.recv(), Some("world"));
+    /// Blocks until at least `k` items are queued, then moves every queued
+    /// item into `out`.
+    ///
+    /// Watermark-triggered batching: the consumer wakes once per batch
+    /// rather than once per item. `k` is capped at the channel's capacity
+    /// (a full channel can't grow further). If the Sender disconnects first,
+    /// whatever is left is drained instead. Returns how many were received.
+    pub fn recv_at_least(&self, k: usize, out: &mut Vec<T>) -> usize {
+        let k = k.min(self.shared.buffer.capacity());
+        if self.shared.buffer.len() < k {
+            let mut guard = self.shared.lock.lock().unwrap();
+            self.shared.announce_sleep();
+            // Every send wakes us; re-check the level each time.
+            while self.shared.buffer.len() < k && !self.is_disconnected() {
+                guard = self.shared.sleep(guard);
+            }
+            self.shared.retire_sleep();
+        }
+        let available = self.shared.buffer.len();
+        self.try_recv_batch(out, available)
     }
 
-    #[test]
+    /// Moves up to `max` queued items into `out` without blocking.
+    ///
+    /// Returns how many were received. The Sender is woken once for the
+    /// whole batch.
+    pub fn try_recv_batch(&self, out: &mut Vec<T>, max: usize) -> usize {
+        let mut received = 0;
+        while received < max {
+            match self.shared.take() {
+                Some(item) => out.push(item),
+                None => break,
+            }
+            received += 1;
+        }
+        if received > 0 {
+            self.shared.wake();
+        }
+        received
+    }
+
+    /// Receives items into `out` for as long as `pred` accepts the next one,
+    /// blocking while the channel is empty.
+    ///
+    /// Stops when `pred` rejects an item, which stays queued (it is only
+    /// peeked), or when the Sender is gone and the channel is drained.
+    /// Takes `&mut self` so `pred` can't hold on to the receiver and take
+    /// the item it is looking at.
+    pub fn recv_while(&mut self, pred: impl Fn(&T) -> bool, out: &mut Vec<T>) {
+        loop {
+            match self.shared.take_if(&pred) {
+                Front::Taken(item) => {
+                    out.push(item);
+                    // Before we might park below: a blocked Sender needs to hear about the room.
+                    self.shared.wake();
+                }
+                Front::Rejected => return,
+                Front::Empty => {
+                    if !self.wait_nonempty() {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Receives an item (blocking like `recv`) along with the number of items
+    /// still queued after it.
+    ///
+    /// Returns `None` if the `Sender` has been dropped and the channel is drained.
+    pub fn recv_with_backlog(&self) -> Option<(T, usize)> {
+        let item = self.recv()?;
+        Some((item, self.shared.buffer.backlog()))
+    }
+
+    /// Receives an item like [`recv`](Self::recv), but gives up and returns
+    /// `None` once `cancel` is set.
+    ///
+    /// For coordinated shutdown across many channels without a polling
+    /// loop. A parked receiver only notices the flag when woken, so the
+    /// shutdown code must set `cancel` and then call [`Notifier::notify`]
+    /// on every channel's notifier. `cancel` is checked before each attempt,
+    /// so once set, queued items are left where they are.
+    pub fn recv_cancellable(&self, cancel: &AtomicBool) -> Option<T> {
+        // 1. Fast Path: Try a lock-free receive.
+        if cancel.load(Ordering::Acquire) {
+            return None;
+        }
+        if let Some(item) = self.try_recv() {
+            return Some(item);
+        }
+
+        // 2. Slow Path: Sleep until data, a disconnect, or a cancel wakes us.
+        let mut guard = self.shared.lock.lock().unwrap();
+        self.shared.announce_sleep();
+        let item = loop {
+            // After `announce_sleep`'s fence: a canceller whose `notify`
+            // saw no sleepers stored the flag before we read it here.
+            if cancel.load(Ordering::Acquire) {
+                break None;
+            }
+            if let Some(item) = self.shared.buffer.recv() {
+                break Some(item);
+            }
+            if self.is_disconnected() {
+                break self.shared.buffer.recv();
+            }
+            guard = self.shared.sleep(guard);
+        };
+        self.shared.retire_sleep();
+        drop(guard);
+
+        if item.is_some() {
+            self.shared.wake();
+        }
+        item
+    }
+
+    /// Receives an item, blocking for at most `timeout`.
+    ///
+    /// Returns `Err(Timeout)` if nothing arrived in time, or
+    /// `Err(Disconnected)` if the Sender is gone and the channel is drained.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        match Instant::now().checked_add(timeout) {
+            Some(deadline) => self.recv_deadline(deadline),
+            // A timeout too large to represent is effectively "forever".
+            None => self.recv().ok_or(RecvTimeoutError::Disconnected),
+        }
+    }
+
+    /// Receives an item, blocking until the absolute `deadline` at the latest.
+    ///
+    /// Spurious wake-ups simply re-check against the same deadline, so this
+    /// composes cleanly with loops that already track an absolute instant.
+    pub fn recv_deadline(&self, deadline: Instant) -> Result<T, RecvTimeoutError> {
+        // 1. Fast Path: Try a lock-free receive.
+        if let Some(item) = self.try_recv() {
+            return Ok(item);
+        }
+
+        // 2. Slow Path: Sleep until data arrives or the deadline passes.
+        let mut guard = self.shared.lock.lock().unwrap();
+        self.shared.announce_sleep();
+        let result = loop {
+            if let Some(item) = self.shared.buffer.recv() {
+                break Ok(item);
+            }
+            if self.is_disconnected() {
+                break self
+                    .shared
+                    .buffer
+                    .recv()
+                    .ok_or(RecvTimeoutError::Disconnected);
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                break Err(RecvTimeoutError::Timeout);
+            }
+            guard = self.shared.sleep_timeout(guard, deadline - now);
+        };
+        self.shared.retire_sleep();
+        drop(guard);
+
+        if result.is_ok() {
+            self.shared.wake();
+        }
+        result
+    }
+
+    /// Polls for an item, for use in a hand-written `Future` or executor.
+    ///
+    /// Returns `Ready(Some(item))` if one is queued, `Ready(None)` once the
+    /// Sender is gone and the channel is drained, and otherwise `Pending`
+    /// after registering `cx`'s waker, which the next send (or the Sender's
+    /// disconnect) wakes. Only the most recent waker is kept.
+    pub fn poll_recv(&self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        if let Some(item) = self.try_recv() {
+            return Poll::Ready(Some(item));
+        }
+        if self.is_disconnected() {
+            return Poll::Ready(self.try_recv());
+        }
+
+        self.shared.register_waker(cx.waker());
+        // Re-check: a send that raced with the registration may not have
+        // seen the waker.
+        if let Some(item) = self.try_recv() {
+            return Poll::Ready(Some(item));
+        }
+        if self.is_disconnected() {
+            return Poll::Ready(self.try_recv());
+        }
+        Poll::Pending
+    }
+
+    /// Returns a blocking iterator over received items.
+    ///
+    /// It ends (yields `None`) once the Sender is gone and the channel is drained.
+    pub fn iter(&self) -> Iter<'_, T, M> {
+        Iter { rx: self }
+    }
+
+    /// Returns the reason passed to [`Sender::close_with_reason`], or `None`
+    /// if the channel is still open or ended cleanly (plain `close` or drop).
+    pub fn disconnect_reason(&self) -> Option<&str> {
+        if !self.shared.disconnected.load(Ordering::Acquire) {
+            return None;
+        }
+        self.shared.close_reason.get().map(String::as_str)
+    }
+
+    /// Returns the id of this channel (the same as its Sender's).
+    pub fn id(&self) -> ChannelId {
+        self.shared.id
+    }
+
+    /// Returns a [`Notifier`] for waking this channel's parked threads.
+    pub fn notifier(&self) -> Notifier<T, M> {
+        Notifier {
+            shared: Arc::downgrade(&self.shared),
+        }
+    }
+
+    /// Returns a snapshot of the channel's counters in one call.
+    pub fn metrics(&self) -> ChannelMetrics {
+        self.shared.metrics()
+    }
+
+    /// Locks the shared metadata and runs `f` on it.
+    ///
+    /// This takes the same lock the blocking slow path uses, so keep `f` short.
+    pub fn with_metadata<R>(&self, f: impl FnOnce(&mut M) -> R) -> R {
+        f(&mut self.shared.lock.lock().unwrap())
+    }
+
+    /// Returns true once the Sender is gone.
+    fn is_disconnected(&self) -> bool {
+        // Check the flag first; if we are the *only* Arc owner left,
+        // the Sender must be gone too.
+        self.shared.disconnected.load(Ordering::Acquire) || Arc::strong_count(&self.shared) == 1
+    }
+}
+
+/// Borrowing iterator returned by [`Receiver::iter`]. Blocks like `recv`.
+pub struct Iter<'a, T, M = ()> {
+    rx: &'a Receiver<T, M>,
+}
+
+impl<T, M> Iterator for Iter<'_, T, M> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.rx.recv()
+    }
+}
+
+/// Owning iterator over a [`Receiver`]. Blocks like `recv`.
+pub struct IntoIter<T, M = ()> {
+    rx: Receiver<T, M>,
+}
+
+impl<T, M> Iterator for IntoIter<T, M> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.rx.recv()
+    }
+}
+
+impl<T, M> IntoIterator for Receiver<T, M> {
+    type Item = T;
+    type IntoIter = IntoIter<T, M>;
+
+    fn into_iter(self) -> IntoIter<T, M> {
+        IntoIter { rx: self }
+    }
+}
+
+impl<'a, T, M> IntoIterator for &'a Receiver<T, M> {
+    type Item = T;
+    type IntoIter = Iter<'a, T, M>;
+
+    fn into_iter(self) -> Iter<'a, T, M> {
+        self.iter()
+    }
+}
+
+/// A [`Receiver`] that several threads can pull from (competing consumers).
+///
+/// Each `recv`/`try_recv` locks, takes one item, and unlocks, so work is
+/// spread across the workers and every item is consumed exactly once. This
+/// is the logger's mutex-wrapped-sender trick applied to the consumer side:
+/// the buffer stays SPSC, and the lock serializes the consumers.
+pub struct SharedReceiver<T, M = ()> {
+    inner: Arc<Mutex<Receiver<T, M>>>,
+}
+
+impl<T, M> Clone for SharedReceiver<T, M> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T, M> SharedReceiver<T, M> {
+    /// Wraps a receiver so it can be cloned across worker threads.
+    pub fn new(rx: Receiver<T, M>) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(rx)),
+        }
+    }
+
+    /// Receives an item, blocking like [`Receiver::recv`].
+    ///
+    /// Other workers wait on the lock meanwhile, so at most one thread is
+    /// ever parked on the channel itself.
+    pub fn recv(&self) -> Option<T> {
+        self.inner.lock().unwrap().recv()
+    }
+
+    /// Receives an item if one is available, without blocking on the channel.
+    pub fn try_recv(&self) -> Option<T> {
+        self.inner.lock().unwrap().try_recv()
+    }
+}
+
+impl<T, M> Drop for Sender<T, M> {
+    fn drop(&mut self) {
+        // When the sender drops, we must wake up any
+        // sleeping receiver so it can check for disconnection.
+        self.close();
+    }
+}
+
+// --- Tests ---
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    fn assert_send<T: Send>() {}
+
+    #[test]
+    fn test_auto_traits() {
+        // Compile-time checks: both halves can be moved to another thread.
+        assert_send::<Sender<u64>>();
+        assert_send::<Receiver<u64>>();
+        assert_send::<Sender<String, Option<String>>>();
+        assert_send::<Receiver<String, Option<String>>>();
+    }
+
+    #[test]
+    fn test_blocking_send_recv() {
+        let (tx, rx) = channel(1); // Capacity of 1
+
+        // Send one item, should be fine.
+        tx.send("hello");
+
+        // Spawn a producer that will block
+        let tx_clone = tx.shared.clone(); // Use Arc for test
+        let _producer = thread::spawn(move || {
+            let sender = Sender {
+                shared: tx_clone,
+                sent: AtomicU64::new(0),
+            };
+            sender.send("world");
+            // This thread is now blocked
+        });
+
+        // Wait a moment
+        thread::sleep(Duration::from_millis(50));
+
+        // Now, receive an item, which should unblock the producer
+        assert_eq!(rx.recv(), Some("hello"));
+        assert_eq!(rx.recv(), Some("world"));
+    }
+
+    #[test]
     fn test_blocking_recv() {
         let (tx, rx) = channel(4);
 
@@ -203,6 +1302,39 @@ mod tests {
         producer.join().unwrap();
     }
 
+    #[test]
+    fn test_wake_waits_for_announced_sleeper() {
+        // Play a receiver that has announced itself and re-checked the
+        // buffer, but hasn't reached `wait` yet. The sender's wake-up must
+        // not fire into that gap, or the receiver sleeps forever.
+        let (tx, rx) = channel(1);
+        let shared = rx.shared.clone();
+        let guard = shared.lock.lock().unwrap();
+        shared.announce_sleep();
+        let sender = thread::spawn(move || tx.send(1));
+        thread::sleep(Duration::from_millis(50));
+        assert!(!sender.is_finished(), "wake didn't wait for the sleeper");
+
+        // Only once we are inside `wait` can the sender lock and notify.
+        let guard = shared.sleep(guard);
+        shared.retire_sleep();
+        drop(guard);
+        sender.join().unwrap();
+        assert_eq!(rx.recv(), Some(1));
+    }
+
+    #[test]
+    fn test_parked_recv_sees_disconnect() {
+        let (tx, rx) = channel::<u32>(4);
+        // Stands in for the Sender's own `Arc`, which is released only after
+        // its `Drop` has sent the wake-up: the strong count can't be trusted.
+        let _pending = tx.shared.clone();
+        let receiver = thread::spawn(move || rx.recv());
+        thread::sleep(Duration::from_millis(20));
+        drop(tx);
+        assert_eq!(receiver.join().unwrap(), None);
+    }
+
     #[test]
     fn test_disconnection() {
         let (tx, rx) = channel(4);
@@ -220,4 +1352,594 @@ mod tests {
         // recv() should return None.
         assert_eq!(rx.recv(), None);
     }
+
+    #[test]
+    fn test_sender_dropped_before_any_send() {
+        // Each receive flavour must report disconnection, not block or panic.
+        let (tx, rx) = channel::<u32>(4);
+        drop(tx);
+        assert_eq!(rx.try_recv(), None);
+        assert_eq!(rx.try_recv_err(), Err(Error::Disconnected));
+        assert_eq!(rx.recv(), None);
+        assert!(!rx.wait_nonempty());
+        assert_eq!(
+            rx.recv_timeout(Duration::from_secs(10)),
+            Err(RecvTimeoutError::Disconnected)
+        );
+        assert_eq!(
+            rx.recv_timeout(Duration::MAX),
+            Err(RecvTimeoutError::Disconnected)
+        );
+        // Disconnection wins over an already-expired deadline.
+        assert_eq!(
+            rx.recv_deadline(Instant::now()),
+            Err(RecvTimeoutError::Disconnected)
+        );
+        assert_eq!(rx.iter().next(), None);
+        assert_eq!((&rx).into_iter().count(), 0);
+        assert_eq!(rx.into_iter().count(), 0);
+
+        // The busy-polling slow path too.
+        let (tx, rx) = ChannelBuilder::new().park_on_empty(false).build::<u32>();
+        drop(tx);
+        assert_eq!(rx.recv(), None);
+
+        // And a Sender that leaves while the receiver is already blocked.
+        let (tx, rx) = channel::<u32>(4);
+        let dropper = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            drop(tx);
+        });
+        assert_eq!(rx.into_iter().next(), None);
+        dropper.join().unwrap();
+    }
+
+    #[test]
+    fn test_iter_drains_until_disconnect() {
+        let (tx, rx) = channel(4);
+        let producer = thread::spawn(move || {
+            for i in 0..100 {
+                tx.send(i);
+            }
+        });
+        let got: Vec<i32> = rx.into_iter().collect();
+        producer.join().unwrap();
+        assert_eq!(got, (0..100).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_try_send_or_replace_keeps_latest() {
+        let (tx, rx) = ChannelBuilder::new()
+            .capacity(1)
+            .coalescing(true)
+            .build::<u32>();
+
+        assert_eq!(tx.try_send_or_replace(1), None);
+        // Saturated: every update overwrites the single queued value.
+        for next in 2..=5 {
+            assert_eq!(tx.try_send_or_replace(next), Some(next - 1));
+        }
+        assert_eq!(rx.try_recv(), Some(5));
+        assert_eq!(rx.try_recv(), None);
+
+        // Once drained, it's a plain send again.
+        assert_eq!(tx.try_send_or_replace(6), None);
+        assert_eq!(rx.recv(), Some(6));
+    }
+
+    #[test]
+    fn test_try_send_or_replace_concurrent_reader() {
+        let (tx, rx) = ChannelBuilder::new()
+            .capacity(2)
+            .coalescing(true)
+            .build::<u64>();
+        let producer = thread::spawn(move || {
+            for i in 1..=10_000 {
+                tx.try_send_or_replace(i);
+            }
+        });
+
+        // Values may be skipped, but never reordered, and the last always arrives.
+        let mut last = 0;
+        for v in rx.iter() {
+            assert!(v > last, "{} after {}", v, last);
+            last = v;
+        }
+        producer.join().unwrap();
+        assert_eq!(last, 10_000);
+    }
+
+    #[test]
+    fn test_recv_while_stops_at_sentinel() {
+        let (tx, mut rx) = channel(8);
+        for x in [1, 2, 3, -1, 4] {
+            tx.send(x);
+        }
+        let mut out = Vec::new();
+        rx.recv_while(|x| *x >= 0, &mut out);
+        assert_eq!(out, [1, 2, 3]);
+        // The sentinel was only peeked.
+        assert_eq!(rx.try_recv(), Some(-1));
+        assert_eq!(rx.try_recv(), Some(4));
+    }
+
+    #[test]
+    fn test_recv_while_blocks_until_disconnect() {
+        let (tx, mut rx) = channel(2);
+        let producer = thread::spawn(move || {
+            for i in 0..10 {
+                tx.send(i);
+                thread::sleep(Duration::from_millis(1));
+            }
+        });
+        let mut out = Vec::new();
+        rx.recv_while(|_| true, &mut out);
+        producer.join().unwrap();
+        assert_eq!(out, (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_poll_recv_transitions() {
+        use std::task::Wake;
+
+        struct CountingWaker(AtomicUsize);
+        impl Wake for CountingWaker {
+            fn wake(self: Arc<Self>) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let counter = Arc::new(CountingWaker(AtomicUsize::new(0)));
+        let waker = Waker::from(counter.clone());
+        let mut cx = Context::from_waker(&waker);
+        let (tx, rx) = channel(4);
+
+        assert_eq!(rx.poll_recv(&mut cx), Poll::Pending);
+        tx.send(1);
+        assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+        tx.send(2);
+        // The waker was consumed by the first send.
+        assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+
+        assert_eq!(rx.poll_recv(&mut cx), Poll::Ready(Some(1)));
+        assert_eq!(rx.poll_recv(&mut cx), Poll::Ready(Some(2)));
+        assert_eq!(rx.poll_recv(&mut cx), Poll::Pending);
+
+        // Disconnecting wakes the receiver, which then sees the end.
+        drop(tx);
+        assert_eq!(counter.0.load(Ordering::SeqCst), 2);
+        assert_eq!(rx.poll_recv(&mut cx), Poll::Ready(None));
+
+        let mut noop = Context::from_waker(Waker::noop());
+        assert_eq!(rx.poll_recv(&mut noop), Poll::Ready(None));
+    }
+
+    #[test]
+    fn test_high_water_fires_once_per_excursion() {
+        let (tx, rx) = channel(8);
+        let fired = Arc::new(AtomicUsize::new(0));
+        let counter = fired.clone();
+        tx.set_high_water(6, move || {
+            counter.fetch_add(1, Ordering::Relaxed);
+        });
+
+        for i in 0..8 {
+            tx.send(i);
+        }
+        // 7 and 8 are both past 6, but the callback fires only once.
+        assert_eq!(fired.load(Ordering::Relaxed), 1);
+
+        // Recede, then cross again.
+        while rx.try_recv().is_some() {}
+        tx.send(0);
+        assert_eq!(fired.load(Ordering::Relaxed), 1);
+        for i in 0..6 {
+            tx.send(i);
+        }
+        assert_eq!(fired.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_shared_receiver_competing_consumers() {
+        let (tx, rx) = channel(16);
+        let rx = SharedReceiver::new(rx);
+
+        let workers: Vec<_> = (0..3)
+            .map(|_| {
+                let rx = rx.clone();
+                thread::spawn(move || std::iter::from_fn(|| rx.recv()).collect::<Vec<u32>>())
+            })
+            .collect();
+
+        for i in 0..300 {
+            tx.send(i);
+        }
+        drop(tx);
+
+        let mut all: Vec<u32> = workers
+            .into_iter()
+            .flat_map(|w| w.join().unwrap())
+            .collect();
+        all.sort_unstable();
+        assert_eq!(all, (0..300).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_wait_empty_returns_after_last_item() {
+        let (tx, rx) = channel(8);
+        for i in 0..8 {
+            tx.try_send(i).unwrap();
+        }
+
+        let consumer = thread::spawn(move || {
+            for i in 0..8 {
+                thread::sleep(Duration::from_millis(2));
+                assert_eq!(rx.recv(), Some(i));
+            }
+            rx
+        });
+
+        let started = Instant::now();
+        tx.wait_empty();
+        // Returned only once all 8 slow receives had happened.
+        assert!(tx.shared.buffer.is_empty());
+        assert!(started.elapsed() >= Duration::from_millis(16));
+
+        let rx = consumer.join().unwrap();
+        assert_eq!(rx.try_recv(), None);
+        // Already empty: returns immediately.
+        tx.wait_empty();
+    }
+
+    #[test]
+    fn test_recv_map() {
+        let (tx, rx) = channel(4);
+        tx.send(1);
+        tx.send(-20);
+        drop(tx);
+
+        assert_eq!(rx.recv_map(|i: i32| i.to_string()), Some("1".to_string()));
+        assert_eq!(rx.recv_map(|i| i.to_string()), Some("-20".to_string()));
+        assert_eq!(rx.recv_map(|i| i.to_string()), None);
+    }
+
+    #[test]
+    fn test_channel_ids() {
+        let (tx1, rx1) = channel::<u8>(2);
+        let (tx2, rx2) = channel::<u8>(2);
+        assert_eq!(tx1.id(), rx1.id());
+        assert_eq!(tx2.id(), rx2.id());
+        assert_ne!(tx1.id(), tx2.id());
+    }
+
+    #[test]
+    fn test_wait_nonempty_then_batch_drain() {
+        let (tx, rx) = channel(8);
+        let producer = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            tx.send(1);
+            tx.send(2);
+            tx
+        });
+
+        assert!(rx.wait_nonempty());
+        let tx = producer.join().unwrap();
+
+        // Waiting didn't consume anything.
+        let mut batch = Vec::new();
+        assert_eq!(rx.try_recv_batch(&mut batch, 16), 2);
+        assert_eq!(batch, vec![1, 2]);
+
+        drop(tx);
+        assert!(!rx.wait_nonempty());
+    }
+
+    #[test]
+    fn test_try_send_all_returns_leftovers() {
+        let (tx, rx) = channel(2);
+        let leftover = tx.try_send_all(vec![1, 2, 3, 4, 5]);
+        assert_eq!(leftover, vec![3, 4, 5]);
+
+        assert_eq!(rx.try_recv(), Some(1));
+        assert_eq!(rx.try_recv(), Some(2));
+        assert_eq!(tx.try_send_all(leftover), vec![5]);
+    }
+
+    #[test]
+    fn test_err_variants() {
+        let (tx, rx) = channel(1);
+        assert_eq!(rx.try_recv_err(), Err(Error::Empty));
+
+        tx.try_send_err(1).unwrap();
+        assert_eq!(tx.try_send_err(2), Err(Error::Full));
+
+        drop(tx);
+        assert_eq!(rx.try_recv_err(), Ok(1));
+        assert_eq!(rx.try_recv_err(), Err(Error::Disconnected));
+
+        let (tx, rx) = channel::<u8>(1);
+        drop(rx);
+        assert_eq!(tx.try_send_err(1), Err(Error::Disconnected));
+    }
+
+    #[test]
+    fn test_close_keeps_sender_alive() {
+        let (tx, rx) = channel(4);
+        tx.send(1);
+        tx.send(2);
+
+        tx.close();
+        assert!(tx.is_closed());
+
+        // The sender is still alive, but the stream has logically ended.
+        assert_eq!(rx.recv(), Some(1));
+        assert_eq!(rx.recv(), Some(2));
+        assert_eq!(rx.recv(), None);
+
+        drop(tx);
+    }
+
+    #[test]
+    fn test_close_wakes_blocked_receiver() {
+        let (tx, rx) = channel::<i32>(4);
+        let consumer = thread::spawn(move || rx.recv());
+
+        thread::sleep(Duration::from_millis(50));
+        tx.close();
+
+        assert_eq!(consumer.join().unwrap(), None);
+        drop(tx);
+    }
+
+    #[test]
+    fn test_recv_deadline_times_out() {
+        let (tx, rx) = channel::<i32>(4);
+
+        let deadline = Instant::now() + Duration::from_millis(50);
+        assert_eq!(rx.recv_deadline(deadline), Err(RecvTimeoutError::Timeout));
+
+        let now = Instant::now();
+        assert!(now >= deadline);
+        assert!(now - deadline < Duration::from_millis(500), "overslept");
+
+        drop(tx);
+    }
+
+    #[test]
+    fn test_recv_timeout() {
+        let (tx, rx) = channel(4);
+
+        let producer = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            tx.send(5);
+        });
+
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)), Ok(5));
+        producer.join().unwrap();
+        assert_eq!(
+            rx.recv_timeout(Duration::from_millis(10)),
+            Err(RecvTimeoutError::Disconnected)
+        );
+    }
+
+    #[test]
+    fn test_send_timeout() {
+        let (tx, rx) = channel(1);
+        tx.send(1);
+
+        // Nobody drains: the item comes back after the deadline.
+        let start = Instant::now();
+        assert_eq!(tx.send_timeout(2, Duration::from_millis(30)), Err(2));
+        assert!(start.elapsed() >= Duration::from_millis(30));
+
+        // A receive during the wait makes room in time.
+        let consumer = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            assert_eq!(rx.recv(), Some(1));
+            rx
+        });
+        assert_eq!(tx.send_timeout(3, Duration::from_secs(5)), Ok(()));
+        let rx = consumer.join().unwrap();
+        assert_eq!(rx.recv(), Some(3));
+    }
+
+    #[test]
+    fn test_recv_at_least_waits_for_watermark() {
+        let (tx, rx) = channel(16);
+        let producer = thread::spawn(move || {
+            for i in 0..7 {
+                tx.send(i);
+                thread::sleep(Duration::from_millis(5));
+            }
+        });
+
+        let mut out = Vec::new();
+        // Woken only once four items have accumulated.
+        assert!(rx.recv_at_least(4, &mut out) >= 4);
+        assert_eq!(out[..4], [0, 1, 2, 3]);
+
+        // The Sender hangs up before the next watermark: drain the rest.
+        while rx.recv_at_least(4, &mut out) > 0 {}
+        producer.join().unwrap();
+        assert_eq!(out, (0..7).collect::<Vec<_>>());
+        assert_eq!(rx.recv_at_least(4, &mut out), 0);
+    }
+
+    #[test]
+    fn test_close_with_reason() {
+        let (tx, rx) = channel(4);
+        tx.send(1);
+        assert_eq!(rx.disconnect_reason(), None);
+
+        let producer = thread::spawn(move || {
+            tx.send(2);
+            tx.close_with_reason("feed error: connection reset");
+        });
+        assert_eq!(rx.iter().collect::<Vec<_>>(), vec![1, 2]);
+        producer.join().unwrap();
+        assert_eq!(rx.disconnect_reason(), Some("feed error: connection reset"));
+
+        // A clean end of stream has no reason.
+        let (tx, rx) = channel::<i32>(4);
+        drop(tx);
+        assert_eq!(rx.recv(), None);
+        assert_eq!(rx.disconnect_reason(), None);
+    }
+
+    #[test]
+    fn test_channel_from_buffer() {
+        let buffer = RingBuffer::try_new_exact(8).unwrap();
+        buffer.send(1).unwrap();
+
+        let (tx, rx) = channel_from_buffer(buffer);
+        assert_eq!(tx.metrics().capacity, 8);
+
+        let producer = thread::spawn(move || {
+            for i in 2..=20 {
+                tx.send(i);
+            }
+        });
+        // The item queued before wrapping comes first.
+        assert_eq!(rx.iter().collect::<Vec<_>>(), (1..=20).collect::<Vec<_>>());
+        producer.join().unwrap();
+    }
+
+    #[test]
+    fn test_recv_cancellable_wakes_on_cancel() {
+        let (tx, rx) = channel::<i32>(4);
+        let cancel = Arc::new(AtomicBool::new(false));
+        let notifier = rx.notifier();
+
+        tx.send(1);
+        assert_eq!(rx.recv_cancellable(&cancel), Some(1));
+
+        let consumer = {
+            let cancel = cancel.clone();
+            thread::spawn(move || rx.recv_cancellable(&cancel))
+        };
+        // Let the consumer park (nothing is coming).
+        thread::sleep(Duration::from_millis(20));
+        cancel.store(true, Ordering::Release);
+        notifier.notify();
+
+        assert_eq!(consumer.join().unwrap(), None);
+        // The Sender is still alive: this was a cancel, not a disconnect.
+        assert!(!tx.is_closed());
+    }
+
+    #[test]
+    fn test_sent_count_per_producer() {
+        let (senders, mut fan) = fan_in::<u32>(2, 4);
+        for i in 0..3 {
+            senders[0].send(i);
+        }
+        assert_eq!(senders[1].try_send_all(vec![10, 11, 12, 13, 14]), vec![14]);
+        assert_eq!(senders[1].try_send(15), Err(15));
+
+        assert_eq!(senders[0].sent_count(), 3);
+        assert_eq!(senders[1].sent_count(), 4);
+
+        let mut received = 0;
+        while fan.try_recv().is_some() {
+            received += 1;
+        }
+        assert_eq!(received, 7);
+    }
+
+    #[test]
+    fn test_metrics_snapshot() {
+        let (tx, rx) = channel(4);
+        for i in 0..6 {
+            let _ = tx.try_send(i);
+        }
+        assert_eq!(rx.recv(), Some(0));
+        assert_eq!(rx.recv(), Some(1));
+
+        let metrics = tx.metrics();
+        assert_eq!(metrics.occupancy, 2);
+        assert_eq!(metrics.capacity, 4);
+        assert_eq!(metrics.sent, 4);
+        assert_eq!(metrics.received, 2);
+        #[cfg(feature = "stats")]
+        assert_eq!(metrics.rejected, 2);
+        assert_eq!(rx.metrics(), metrics);
+    }
+
+    #[test]
+    #[cfg(feature = "stats")]
+    fn test_replaced_sends_are_not_rejected() {
+        let (tx, rx) = ChannelBuilder::new().capacity(2).coalescing(true).build();
+        for i in 0..5 {
+            let _ = tx.try_send_or_replace(i);
+        }
+        assert_eq!(tx.metrics().rejected, 0);
+
+        assert_eq!(tx.try_send(9), Err(9));
+        assert_eq!(rx.metrics().rejected, 1);
+    }
+
+    #[test]
+    fn test_recv_with_backlog() {
+        let (tx, rx) = channel(8);
+        for i in 0..5 {
+            tx.send(i);
+        }
+        drop(tx);
+
+        let backlogs: Vec<usize> = std::iter::from_fn(|| rx.recv_with_backlog())
+            .map(|(_, backlog)| backlog)
+            .collect();
+        assert_eq!(backlogs, vec![4, 3, 2, 1, 0]);
+    }
+
+    #[test]
+    fn test_metadata_shutdown_reason() {
+        let (tx, rx) = ChannelBuilder::new()
+            .capacity(4)
+            .build_with_metadata::<i32, Option<String>>();
+
+        tx.send(1);
+        tx.with_metadata(|reason| *reason = Some("upstream EOF".to_string()));
+        tx.close();
+
+        assert_eq!(rx.recv(), Some(1));
+        assert_eq!(rx.recv(), None);
+        let reason = rx.with_metadata(|reason| reason.clone());
+        assert_eq!(reason.as_deref(), Some("upstream EOF"));
+    }
+
+    #[test]
+    fn test_builder_custom_spin() {
+        let (tx, rx) = ChannelBuilder::new().capacity(2).spin_iters(1_000).build();
+
+        let producer = thread::spawn(move || {
+            for i in 0..100 {
+                tx.send(i);
+            }
+        });
+
+        for i in 0..100 {
+            assert_eq!(rx.recv(), Some(i));
+        }
+        producer.join().unwrap();
+        assert_eq!(rx.recv(), None);
+    }
+
+    #[test]
+    fn test_builder_busy_poll() {
+        let (tx, rx) = ChannelBuilder::new()
+            .capacity(4)
+            .spin_iters(10)
+            .park_on_empty(false)
+            .build();
+
+        let producer = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            tx.send(7);
+        });
+
+        assert_eq!(rx.recv(), Some(7));
+        producer.join().unwrap();
+        assert_eq!(rx.recv(), None);
+    }
 }
@@ -0,0 +1,98 @@
+//! A crate-wide error type.
+//!
+//! Most primitives keep their cheap, allocation-free return types (`Result<(), T>`,
+//! `Option<T>`) on the hot path. The `*_err` variants return [`Error`] instead,
+//! so you can use `?` uniformly across modules when that matters more than
+//! getting the rejected item back.
+
+use std::fmt;
+
+/// The unified error returned by the `*_err` methods across the crate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The buffer, channel, or pool has no room for another item.
+    Full,
+    /// There was nothing to take.
+    Empty,
+    /// The other half of a channel is gone.
+    Disconnected,
+    /// A blocking operation hit its deadline.
+    Timeout,
+    /// The backing storage could not be allocated, or an arena ran out of space.
+    OutOfMemory,
+    /// An exact capacity was requested but it is not a power of two.
+    CapacityNotPowerOfTwo,
+    /// Some pooled objects are still checked out.
+    InUse,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Full => write!(f, "no space left"),
+            Error::Empty => write!(f, "nothing to take"),
+            Error::Disconnected => write!(f, "the other side is disconnected"),
+            Error::Timeout => write!(f, "operation timed out"),
+            Error::OutOfMemory => write!(f, "out of memory"),
+            Error::CapacityNotPowerOfTwo => write!(f, "capacity must be a power of two"),
+            Error::InUse => write!(f, "objects are still in use"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<crate::ring_buffer::AllocError> for Error {
+    fn from(_: crate::ring_buffer::AllocError) -> Self {
+        Error::OutOfMemory
+    }
+}
+
+#[cfg(feature = "channel")]
+impl From<crate::channel::RecvTimeoutError> for Error {
+    fn from(e: crate::channel::RecvTimeoutError) -> Self {
+        match e {
+            crate::channel::RecvTimeoutError::Timeout => Error::Timeout,
+            crate::channel::RecvTimeoutError::Disconnected => Error::Disconnected,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let cases = [
+            (Error::Full, "no space left"),
+            (Error::Empty, "nothing to take"),
+            (Error::Disconnected, "the other side is disconnected"),
+            (Error::Timeout, "operation timed out"),
+            (Error::OutOfMemory, "out of memory"),
+            (
+                Error::CapacityNotPowerOfTwo,
+                "capacity must be a power of two",
+            ),
+            (Error::InUse, "objects are still in use"),
+        ];
+        for (err, expected) in cases {
+            assert_eq!(err.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn test_question_mark_across_modules() {
+        fn roundtrip() -> Result<u32, Error> {
+            let rb = crate::RingBuffer::try_new_exact(4)?;
+            rb.send_err(7)?;
+            rb.recv_err()
+        }
+        assert_eq!(roundtrip(), Ok(7));
+
+        assert_eq!(
+            crate::RingBuffer::<u8>::try_new_exact(3).err(),
+            Some(Error::CapacityNotPowerOfTwo)
+        );
+    }
+}
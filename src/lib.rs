@@ -1,13 +1,19 @@
+pub mod error;
+pub use error::Error;
 pub mod ring_buffer;
 pub use ring_buffer::RingBuffer;
 #[cfg(feature = "affinity")]
 pub mod affinity;
 #[cfg(feature = "arena_allocator")]
 pub mod arena_allocator;
+#[cfg(feature = "bench_utils")]
+pub mod bench_utils;
 #[cfg(feature = "channel")]
 pub mod channel;
 #[cfg(feature = "object_pool")]
 pub mod object_pool;
+#[cfg(feature = "slab")]
+pub mod slab;
 
 #[cfg(feature = "logger")]
 pub mod logger;
@@ -0,0 +1,106 @@
+use super::{Logger, LoggerBuilder};
+use std::fmt;
+use std::sync::OnceLock;
+
+/// The process-wide logger installed by [`init_global`].
+static GLOBAL: OnceLock<Logger> = OnceLock::new();
+
+/// The severity prefixed to messages logged through [`global_log`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+        })
+    }
+}
+
+/// Builds a logger from `config` and installs it as the global logger.
+///
+/// Returns `false` (and builds nothing) if a global logger was already
+/// installed: the first call wins for the lifetime of the process.
+pub fn init_global(config: LoggerBuilder) -> bool {
+    let mut installed = false;
+    GLOBAL.get_or_init(|| {
+        installed = true;
+        config.build()
+    });
+    installed
+}
+
+/// Returns the global logger, if [`init_global`] has been called.
+pub fn global() -> Option<&'static Logger> {
+    GLOBAL.get()
+}
+
+/// Logs `msg` through the global logger as `"[LEVEL] msg"`.
+///
+/// Silently does nothing if no global logger is installed, so libraries can
+/// log unconditionally and leave the choice to the application.
+pub fn global_log(level: Level, msg: impl fmt::Display) {
+    if let Some(logger) = GLOBAL.get() {
+        logger.log_fmt(format_args!("[{}] {}", level, msg));
+    }
+}
+
+/// Logs a `format!`-style message at [`Level::Info`] through the global logger.
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)*) => {
+        $crate::logger::global_log($crate::logger::Level::Info, format_args!($($arg)*))
+    };
+}
+
+/// Logs a `format!`-style message at [`Level::Warn`] through the global logger.
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)*) => {
+        $crate::logger::global_log($crate::logger::Level::Warn, format_args!($($arg)*))
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    // The only test that touches the global: it can be installed once per process.
+    #[test]
+    fn test_global_logger_routes_free_functions() {
+        // Logging before initialization is a no-op, not a panic.
+        global_log(Level::Info, "lost");
+
+        let captured = Arc::new(Mutex::new(Vec::new()));
+        let sink_captured = captured.clone();
+        assert!(init_global(
+            Logger::builder()
+                .capacity(16)
+                .sink(move |msg| sink_captured.lock().unwrap().push(msg))
+        ));
+        assert!(!init_global(Logger::builder()));
+
+        global_log(Level::Error, "disk full");
+        crate::info!("order {} filled", 7);
+        crate::warn!("latency {}us", 250);
+        global().unwrap().flush();
+
+        assert_eq!(
+            *captured.lock().unwrap(),
+            vec![
+                "[ERROR] disk full",
+                "[INFO] order 7 filled",
+                "[WARN] latency 250us"
+            ]
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use crate::arena_allocator::Arena;
+use std::alloc::Layout;
+use std::cell::RefCell;
+use std::fmt;
+use std::mem::MaybeUninit;
+use std::{ptr, slice, str};
+
+/// Messages up to this many bytes are copied into the ring slot itself.
+/// Longer ones are carried as a heap `String`.
+pub const INLINE_MESSAGE_LEN: usize = 120;
+
+/// Size of each producer thread's formatting arena. Messages that don't fit
+/// are formatted with `format!` instead.
+pub const FORMAT_SCRATCH_BYTES: usize = 1024;
+
+thread_local! {
+    /// Scratch space `Logger::log_fmt` formats into, reset on every call.
+    static SCRATCH: RefCell<Arena> = RefCell::new(Arena::new(FORMAT_SCRATCH_BYTES));
+}
+
+/// A log line as it travels through the queue.
+pub(super) enum Message {
+    /// Short message stored in place. `bytes[..len]` is valid UTF-8.
+    Inline {
+        len: u8,
+        bytes: [u8; INLINE_MESSAGE_LEN],
+    },
+    Heap(String),
+}
+
+impl Message {
+    /// Copies `s` into a message, allocating only if it is too long to inline.
+    pub(super) fn copy_from(s: &str) -> Self {
+        if s.len() > INLINE_MESSAGE_LEN {
+            return Message::Heap(s.to_owned());
+        }
+        let mut bytes = [0; INLINE_MESSAGE_LEN];
+        bytes[..s.len()].copy_from_slice(s.as_bytes());
+        Message::Inline {
+            len: s.len() as u8,
+            bytes,
+        }
+    }
+
+    /// Formats `args` into this thread's scratch arena, then copies the
+    /// result into a message. Steady state, short messages don't allocate.
+    pub(super) fn format(args: fmt::Arguments<'_>) -> Self {
+        SCRATCH.with(|scratch| {
+            // Re-entrant call (a `Display` impl that logs): don't share the arena.
+            let Ok(mut arena) = scratch.try_borrow_mut() else {
+                return Message::Heap(fmt::format(args));
+            };
+            arena.reset();
+            let region = arena.alloc_layout(Layout::new::<[u8; FORMAT_SCRATCH_BYTES]>());
+            // SAFETY: `alloc_layout` handed us `FORMAT_SCRATCH_BYTES` fresh bytes,
+            // which nothing else touches until the next `reset` (under our borrow).
+            let buf =
+                unsafe { slice::from_raw_parts_mut(region.as_ptr().cast(), FORMAT_SCRATCH_BYTES) };
+
+            let mut out = ScratchWriter { buf, len: 0 };
+            if fmt::write(&mut out, args).is_err() {
+                // Too big for the arena.
+                return Message::Heap(fmt::format(args));
+            }
+            // SAFETY: `write_str` initialized `buf[..len]` from whole `&str`s.
+            let text = unsafe {
+                str::from_utf8_unchecked(slice::from_raw_parts(out.buf.as_ptr().cast(), out.len))
+            };
+            Message::copy_from(text)
+        })
+    }
+
+    pub(super) fn as_str(&self) -> &str {
+        match self {
+            // SAFETY: Inline messages are only built from a `&str` in `copy_from`.
+            Message::Inline { len, bytes } => unsafe {
+                str::from_utf8_unchecked(&bytes[..*len as usize])
+            },
+            Message::Heap(s) => s,
+        }
+    }
+
+    pub(super) fn into_string(self) -> String {
+        match self {
+            Message::Heap(s) => s,
+            inline => inline.as_str().to_owned(),
+        }
+    }
+}
+
+impl From<String> for Message {
+    fn from(s: String) -> Self {
+        Message::Heap(s)
+    }
+}
+
+/// A bounded `fmt::Write` over uninitialized arena memory.
+struct ScratchWriter<'a> {
+    buf: &'a mut [MaybeUninit<u8>],
+    len: usize,
+}
+
+impl fmt::Write for ScratchWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        if end > self.buf.len() {
+            return Err(fmt::Error);
+        }
+        // SAFETY: `end` is in bounds, and `s` can't overlap our arena region.
+        unsafe {
+            ptr::copy_nonoverlapping(
+                s.as_ptr(),
+                self.buf.as_mut_ptr().add(self.len).cast(),
+                s.len(),
+            );
+        }
+        self.len = end;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_inlines_short_and_boxes_long() {
+        let short = Message::format(format_args!("order {} filled @ {:.2}", 42, 101.5));
+        assert!(matches!(short, Message::Inline { .. }));
+        assert_eq!(short.as_str(), "order 42 filled @ 101.50");
+
+        let medium = "x".repeat(INLINE_MESSAGE_LEN + 1);
+        let medium_msg = Message::format(format_args!("{}", medium));
+        assert!(matches!(medium_msg, Message::Heap(_)));
+        assert_eq!(medium_msg.as_str(), medium);
+
+        // Doesn't fit the scratch arena either: falls back to `format!`.
+        let long = "é".repeat(FORMAT_SCRATCH_BYTES);
+        assert_eq!(
+            Message::format(format_args!("{}", long)).into_string(),
+            long
+        );
+
+        // Exactly full is still inline.
+        let exact = "y".repeat(INLINE_MESSAGE_LEN);
+        let exact_msg = Message::format(format_args!("{}", exact));
+        assert!(matches!(exact_msg, Message::Inline { .. }));
+        assert_eq!(exact_msg.into_string(), exact);
+    }
+}
@@ -1,10 +1,686 @@
 #![doc = include_str!("README.md")]
 
+mod global;
+mod message;
+mod per_thread;
+
+pub use global::{Level, global, global_log, init_global};
+pub use message::{FORMAT_SCRATCH_BYTES, INLINE_MESSAGE_LEN};
+pub use per_thread::ThreadLocalLogger;
+
 use crate::affinity;
-use crate::channel::{Sender, channel};
-use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::{Arc, Mutex}; // Added Mutex
+use crate::channel::{Receiver, Sender, channel};
+use message::Message;
+use std::fmt;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, mpsc}; // Added Mutex
 use std::thread;
+use std::time::{Duration, Instant};
+
+/// The default number of messages the worker drains before writing.
+pub const DEFAULT_BATCH_SIZE: usize = 64;
+
+/// The default upper bound on how long the worker spends collecting a batch.
+pub const DEFAULT_MAX_BATCH_LATENCY: Duration = Duration::from_millis(1);
+
+/// Tracks how far the worker has progressed through the queue.
+/// `flush` uses this to wait until everything enqueued so far has been written.
+struct Progress {
+    /// Number of messages successfully pushed into the channel.
+    enqueued: AtomicU64,
+    /// Number of messages the worker has handed to the sink.
+    processed: Mutex<u64>,
+    signal: Condvar,
+}
+
+/// A message in the main queue, stamped with when it was logged.
+struct Queued {
+    /// Nanoseconds since the logger's [`Lag::epoch`].
+    at: u64,
+    msg: Message,
+}
+
+/// Tracks how far behind the worker is, for [`Logger::current_lag`].
+struct Lag {
+    epoch: Instant,
+    /// Enqueue time (`at + 1`) of the oldest message the worker has not
+    /// finished writing, or 0 if it is caught up.
+    oldest: AtomicU64,
+}
+
+impl Lag {
+    fn new() -> Self {
+        Self {
+            epoch: Instant::now(),
+            oldest: AtomicU64::new(0),
+        }
+    }
+
+    fn now(&self) -> u64 {
+        self.epoch.elapsed().as_nanos() as u64
+    }
+
+    /// Records the oldest pending message (`None`: caught up).
+    fn set_oldest(&self, msg: Option<&Queued>) {
+        let oldest = msg.map_or(0, |q| q.at.saturating_add(1));
+        self.oldest.store(oldest, Ordering::Release);
+    }
+}
+
+/// The in-memory sink [`Logger::capture_scope`] swaps in.
+struct Capture {
+    /// While set, the worker appends to `lines` instead of writing to its sink.
+    active: AtomicBool,
+    lines: Mutex<Vec<String>>,
+}
+
+impl Capture {
+    /// Takes the batch if capturing is on. Returns false (leaving the
+    /// batch alone) otherwise.
+    fn try_take(&self, batch: &mut Vec<Message>) -> bool {
+        if !self.active.load(Ordering::Acquire) {
+            return false;
+        }
+        let mut lines = self.lines.lock().unwrap();
+        lines.extend(batch.drain(..).map(Message::into_string));
+        true
+    }
+}
+
+impl Progress {
+    fn new() -> Self {
+        Self {
+            enqueued: AtomicU64::new(0),
+            processed: Mutex::new(0),
+            signal: Condvar::new(),
+        }
+    }
+
+    /// Called by the worker after handing `written` messages to the sink.
+    fn record_processed(&self, written: u64) {
+        // Let any `flush` callers know these messages have been written.
+        let mut processed = self.processed.lock().unwrap();
+        *processed += written;
+        self.signal.notify_all();
+    }
+
+    /// Blocks until everything enqueued so far has been processed.
+    fn wait_caught_up(&self) {
+        self.wait_caught_up_until(None);
+    }
+
+    /// Like `wait_caught_up`, but gives up at `deadline` (if any).
+    /// Returns how many of the awaited messages were still unprocessed.
+    fn wait_caught_up_until(&self, deadline: Option<Instant>) -> u64 {
+        let target = self.enqueued.load(Ordering::Acquire);
+        let mut processed = self.processed.lock().unwrap();
+        while *processed < target {
+            processed = match deadline {
+                None => self.signal.wait(processed).unwrap(),
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        break;
+                    }
+                    self.signal
+                        .wait_timeout(processed, deadline - now)
+                        .unwrap()
+                        .0
+                }
+            };
+        }
+        target.saturating_sub(*processed)
+    }
+}
+
+/// Which console stream the default sink writes to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LogTarget {
+    /// Standard output (the default).
+    #[default]
+    Stdout,
+    /// Standard error. Keeps stdout clean for program output.
+    Stderr,
+}
+
+impl LogTarget {
+    /// Writes an already-formatted batch to whichever stream this target selects.
+    fn emit(self, text: &[u8], stdout: &mut impl Write, stderr: &mut impl Write) -> io::Result<()> {
+        let out: &mut dyn Write = match self {
+            LogTarget::Stdout => stdout,
+            LogTarget::Stderr => stderr,
+        };
+        out.write_all(text)?;
+        out.flush()
+    }
+}
+
+/// When a [`LoggerBuilder::writer`] sink is flushed.
+///
+/// Flushing more often bounds how much of the log an unflushed `BufWriter`
+/// can lose on a crash, at the cost of throughput.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FlushPolicy {
+    /// Write and flush each message on its own (most durable, slowest).
+    EveryMessage,
+    /// One write and one flush per batch (the default).
+    #[default]
+    EveryBatch,
+    /// Only flush on [`Logger::flush`] and when the worker exits.
+    OnDemand,
+}
+
+/// A writer sink shared with the `Logger`, so `flush` can reach it.
+type SharedWriter = Arc<Mutex<Box<dyn Write + Send>>>;
+
+/// Where the worker sends messages.
+enum Sink {
+    /// A user callback, invoked once per message.
+    Callback(Box<dyn Fn(String) + Send>),
+    /// The console, with a `[LOG] ` prefix on every line.
+    Console(LogTarget),
+    /// A byte stream. Each batch is joined into one buffer and written with
+    /// a single `write_all` (per message under `EveryMessage`).
+    Writer(SharedWriter, FlushPolicy),
+}
+
+impl Sink {
+    /// Hands a batch of messages to the sink, leaving `batch` empty.
+    /// `scratch` is a reusable buffer so steady-state batching doesn't allocate.
+    fn write_batch(&mut self, batch: &mut Vec<Message>, scratch: &mut Vec<u8>) {
+        // Logging is best effort: a failing sink must not kill the worker.
+        match self {
+            Sink::Callback(f) => batch.drain(..).for_each(|msg| f(msg.into_string())),
+            Sink::Console(target) => {
+                join_lines(batch, "[LOG] ", scratch);
+                let _ = target.emit(scratch, &mut io::stdout(), &mut io::stderr());
+            }
+            Sink::Writer(out, FlushPolicy::EveryMessage) => {
+                let mut out = out.lock().unwrap();
+                for msg in batch.drain(..) {
+                    scratch.clear();
+                    scratch.extend_from_slice(msg.as_str().as_bytes());
+                    scratch.push(b'\n');
+                    let _ = out.write_all(scratch).and_then(|_| out.flush());
+                }
+            }
+            Sink::Writer(out, policy) => {
+                join_lines(batch, "", scratch);
+                let mut out = out.lock().unwrap();
+                let _ = out.write_all(scratch);
+                if *policy == FlushPolicy::EveryBatch {
+                    let _ = out.flush();
+                }
+            }
+        }
+    }
+
+    /// Called when the worker exits, so `OnDemand` writers aren't left unflushed.
+    fn finish(&mut self) {
+        if let Sink::Writer(out, FlushPolicy::OnDemand) = self {
+            let _ = out.lock().unwrap().flush();
+        }
+    }
+}
+
+fn shared_writer<W: Write + Send + 'static>(writer: W) -> SharedWriter {
+    Arc::new(Mutex::new(Box::new(writer)))
+}
+
+/// Joins a batch into `out` as prefixed, newline-terminated lines.
+fn join_lines(batch: &mut Vec<Message>, prefix: &str, out: &mut Vec<u8>) {
+    out.clear();
+    for msg in batch.drain(..) {
+        out.extend_from_slice(prefix.as_bytes());
+        out.extend_from_slice(msg.as_str().as_bytes());
+        out.push(b'\n');
+    }
+}
+
+/// What `log` does when the ring buffer is full.
+enum Backpressure {
+    /// Drop the message and count it (the default).
+    Drop,
+    /// Drop until the drop rate gets too high, then block instead.
+    Adaptive(AdaptiveState),
+    /// Block for up to this long, then drop.
+    Timeout(Duration),
+}
+
+/// Drop-rate tracking for [`Backpressure::Adaptive`].
+///
+/// "Full" events (a `try_send` that failed) are counted over one-second
+/// windows. Once a window sees more than `max_drops_per_sec` of them, `log`
+/// starts blocking. It reverts to dropping at the first window that stays
+/// at or under the threshold.
+struct AdaptiveState {
+    max_drops_per_sec: u64,
+    window_start: Instant,
+    full_events: u64,
+    blocking: bool,
+}
+
+impl AdaptiveState {
+    const WINDOW: Duration = Duration::from_secs(1);
+
+    fn new(max_drops_per_sec: u64) -> Self {
+        Self {
+            max_drops_per_sec,
+            window_start: Instant::now(),
+            full_events: 0,
+            blocking: false,
+        }
+    }
+
+    /// Starts a new window if the current one has expired, de-escalating if
+    /// the pressure has subsided.
+    fn roll_window(&mut self, now: Instant) {
+        if now.duration_since(self.window_start) >= Self::WINDOW {
+            if self.full_events <= self.max_drops_per_sec {
+                self.blocking = false;
+            }
+            self.full_events = 0;
+            self.window_start = now;
+        }
+    }
+
+    /// Records a full buffer. Returns true if the caller should block.
+    fn on_full(&mut self) -> bool {
+        self.full_events += 1;
+        if self.full_events > self.max_drops_per_sec {
+            self.blocking = true;
+        }
+        self.blocking
+    }
+}
+
+/// The producer side of the logger, shared (behind a Mutex) by every clone.
+struct Producer {
+    tx: Sender<Queued>,
+    backpressure: Backpressure,
+    /// Overflow path to the spill thread, if a spill sink was configured.
+    spill: Option<mpsc::Sender<Message>>,
+}
+
+/// Where the worker thread gets pinned.
+#[derive(Clone, Copy, Debug)]
+enum Pinning {
+    /// Best effort pin to the last core (the default).
+    LastCore,
+    /// Best effort pin to a user-chosen core.
+    Core(affinity::CoreId),
+    /// Leave placement to the OS scheduler.
+    Unpinned,
+}
+
+impl Pinning {
+    /// Pins the calling thread. Failure is ignored (e.g. on macOS).
+    fn apply(self) {
+        let core = match self {
+            // This is usually an efficient E-core or a core far from Core 0/1.
+            Pinning::LastCore => affinity::get_core_ids().last().copied(),
+            Pinning::Core(core) => Some(core),
+            Pinning::Unpinned => None,
+        };
+        if let Some(core) = core {
+            let _ = affinity::pin_to_core(core);
+        }
+    }
+}
+
+/// Configures and creates a [`Logger`].
+///
+/// ```
+/// use llt_rs::logger::LoggerBuilder;
+/// use std::time::Duration;
+///
+/// let logger = LoggerBuilder::new()
+///     .capacity(4096)
+///     .batch_size(128)
+///     .max_batch_latency(Duration::from_millis(5))
+///     .writer(std::io::sink())
+///     .build();
+/// logger.log("ready");
+/// logger.flush();
+/// ```
+pub struct LoggerBuilder {
+    capacity: usize,
+    batch_size: usize,
+    max_batch_latency: Duration,
+    sink: Sink,
+    pinning: Pinning,
+    max_drops_per_sec: Option<u64>,
+    block_timeout: Option<Duration>,
+    spill: Option<Sink>,
+    flush_policy: FlushPolicy,
+    thread_name: String,
+    stack_size: Option<usize>,
+}
+
+/// The default name of the logger's worker thread.
+pub const DEFAULT_THREAD_NAME: &str = "llt-logger";
+
+impl Default for LoggerBuilder {
+    fn default() -> Self {
+        Self {
+            capacity: 1024,
+            batch_size: DEFAULT_BATCH_SIZE,
+            max_batch_latency: DEFAULT_MAX_BATCH_LATENCY,
+            sink: Sink::Console(LogTarget::Stdout),
+            pinning: Pinning::LastCore,
+            max_drops_per_sec: None,
+            block_timeout: None,
+            spill: None,
+            flush_policy: FlushPolicy::default(),
+            thread_name: DEFAULT_THREAD_NAME.to_string(),
+            stack_size: None,
+        }
+    }
+}
+
+impl LoggerBuilder {
+    /// Creates a builder that logs to stdout with the default batching settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the size of the ring buffer (messages). Rounded up to a power of 2.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Sets the maximum number of messages written in one batch.
+    /// A value of 1 disables batching. Values of 0 are treated as 1.
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Sets the longest the worker will spend collecting a batch before
+    /// writing it out, bounding how stale the output can be under load.
+    pub fn max_batch_latency(mut self, max_batch_latency: Duration) -> Self {
+        self.max_batch_latency = max_batch_latency;
+        self
+    }
+
+    /// Sends every message to `sink`, called once per message on the worker.
+    pub fn sink<F>(mut self, sink: F) -> Self
+    where
+        F: Fn(String) + Send + 'static,
+    {
+        self.sink = Sink::Callback(Box::new(sink));
+        self
+    }
+
+    /// Writes messages (newline-terminated) to `writer`, one `write_all`
+    /// per batch, flushed according to [`flush_policy`](Self::flush_policy).
+    pub fn writer<W>(mut self, writer: W) -> Self
+    where
+        W: Write + Send + 'static,
+    {
+        self.sink = Sink::Writer(shared_writer(writer), FlushPolicy::EveryBatch);
+        self
+    }
+
+    /// Sets when a [`writer`](Self::writer) sink is flushed
+    /// (default: [`FlushPolicy::EveryBatch`]). Other sinks ignore it.
+    pub fn flush_policy(mut self, policy: FlushPolicy) -> Self {
+        self.flush_policy = policy;
+        self
+    }
+
+    /// Logs to the console (with a `[LOG] ` prefix) on the chosen stream.
+    /// This is the default sink, targeting stdout.
+    pub fn target(mut self, target: LogTarget) -> Self {
+        self.sink = Sink::Console(target);
+        self
+    }
+
+    /// Chooses where the worker thread is pinned.
+    ///
+    /// `Some(core)` pins it to `core`; `None` leaves it unpinned.
+    /// If never called, the worker is pinned to the last core.
+    /// Pinning is always best effort: failures are silently ignored.
+    pub fn worker_core(mut self, core: Option<affinity::CoreId>) -> Self {
+        self.pinning = match core {
+            Some(core) => Pinning::Core(core),
+            None => Pinning::Unpinned,
+        };
+        self
+    }
+
+    /// Enables adaptive backpressure.
+    ///
+    /// `log` normally drops messages when the buffer is full. With this set,
+    /// once more than `max_drops_per_sec` messages would be dropped within
+    /// one second, `log` switches to *blocking* until the buffer has room,
+    /// so a burst of error context isn't lost. It reverts to dropping once a
+    /// full second passes at or under the threshold.
+    ///
+    /// Replaces [`block_timeout`](Self::block_timeout), if set.
+    pub fn adaptive(mut self, max_drops_per_sec: u64) -> Self {
+        self.max_drops_per_sec = Some(max_drops_per_sec);
+        self.block_timeout = None;
+        self
+    }
+
+    /// Blocks for up to `timeout` when the buffer is full, then drops.
+    ///
+    /// A middle ground between dropping and blocking: `log` waits (with
+    /// `send_timeout`) for the worker to make room, and only counts a drop
+    /// if the deadline expires. The producer's worst-case stall is bounded
+    /// by `timeout` (plus the time waiting on other producers), while short
+    /// bursts lose nothing.
+    ///
+    /// Replaces [`adaptive`](Self::adaptive), if set.
+    pub fn block_timeout(mut self, timeout: Duration) -> Self {
+        self.block_timeout = Some(timeout);
+        self.max_drops_per_sec = None;
+        self
+    }
+
+    /// Spills overflow to `writer` instead of dropping it.
+    ///
+    /// Messages still go through the ring buffer whenever it has room. Only
+    /// when `try_send` fails is the message handed (over an unbounded queue)
+    /// to a second thread that writes it to `writer`, so nothing is lost and
+    /// the common case stays fast. Spilled messages are counted by
+    /// [`Logger::get_spilled_count`] rather than as drops. Ordering between
+    /// the main sink and the spill sink is not preserved.
+    pub fn spill<W>(mut self, writer: W) -> Self
+    where
+        W: Write + Send + 'static,
+    {
+        self.spill = Some(Sink::Writer(shared_writer(writer), FlushPolicy::EveryBatch));
+        self
+    }
+
+    /// Names the worker thread (default [`DEFAULT_THREAD_NAME`]), so it is
+    /// recognizable in profilers and crash dumps. The spill worker, if any,
+    /// gets the same name with a `-spill` suffix.
+    pub fn thread_name(mut self, name: impl Into<String>) -> Self {
+        self.thread_name = name.into();
+        self
+    }
+
+    /// Sets the worker threads' stack size in bytes (default: the platform's).
+    pub fn stack_size(mut self, bytes: usize) -> Self {
+        self.stack_size = Some(bytes);
+        self
+    }
+
+    /// A `thread::Builder` with the configured stack size and `name`.
+    fn thread_builder(&self, name: String) -> thread::Builder {
+        let builder = thread::Builder::new().name(name);
+        match self.stack_size {
+            Some(bytes) => builder.stack_size(bytes),
+            None => builder,
+        }
+    }
+
+    /// Creates the Logger and spawns its background worker thread.
+    ///
+    /// # Panics
+    /// Panics if the worker thread cannot be spawned. Use
+    /// [`try_build`](Self::try_build) to handle that.
+    pub fn build(self) -> Logger {
+        match self.try_build() {
+            Ok(logger) => logger,
+            Err(e) => panic!("failed to spawn logger worker: {}", e),
+        }
+    }
+
+    /// Like [`build`](Self::build), but returns the error if a worker
+    /// thread cannot be spawned.
+    pub fn try_build(mut self) -> io::Result<Logger> {
+        let (tx, rx) = channel(self.capacity);
+
+        let mut on_demand = None;
+        if let Sink::Writer(out, policy) = &mut self.sink {
+            *policy = self.flush_policy;
+            if *policy == FlushPolicy::OnDemand {
+                on_demand = Some(out.clone());
+            }
+        }
+        let dropped = Arc::new(AtomicU64::new(0));
+        let progress = Arc::new(Progress::new());
+        let worker_progress = progress.clone();
+        let capture = Arc::new(Capture {
+            active: AtomicBool::new(false),
+            lines: Mutex::new(Vec::new()),
+        });
+        let worker_capture = capture.clone();
+        let lag = Arc::new(Lag::new());
+        let worker_lag = lag.clone();
+
+        // FIX: Removed unused variable `dropped_clone`
+
+        // Spawn the dedicated logging thread
+        let worker = self.thread_builder(self.thread_name.clone());
+        let spill_worker = self.thread_builder(format!("{}-spill", self.thread_name));
+        let (pinning, sink) = (self.pinning, self.sink);
+        let (batch_size, max_batch_latency) = (self.batch_size, self.max_batch_latency);
+        worker.spawn(move || {
+            // BEST EFFORT: Pin where we were asked to (the last core by default).
+            // The result is ignored so this doesn't crash on macOS.
+            pinning.apply();
+
+            run_worker(
+                rx,
+                sink,
+                batch_size,
+                max_batch_latency,
+                &worker_progress,
+                &worker_capture,
+                &worker_lag,
+            );
+        })?;
+
+        let backpressure = match (self.max_drops_per_sec, self.block_timeout) {
+            (Some(max), _) => Backpressure::Adaptive(AdaptiveState::new(max)),
+            (None, Some(timeout)) => Backpressure::Timeout(timeout),
+            (None, None) => Backpressure::Drop,
+        };
+
+        let spill_progress = Arc::new(Progress::new());
+        let spill = match self.spill {
+            Some(sink) => {
+                let (spill_tx, spill_rx) = mpsc::channel();
+                let worker_progress = spill_progress.clone();
+                spill_worker.spawn(move || {
+                    run_spill_worker(spill_rx, sink, batch_size, &worker_progress)
+                })?;
+                Some(spill_tx)
+            }
+            None => None,
+        };
+
+        Ok(Logger {
+            // Wrap the raw SPSC sender in a Mutex + Arc for thread-safe sharing
+            producer: Arc::new(Mutex::new(Producer {
+                tx,
+                backpressure,
+                spill,
+            })),
+            dropped_count: dropped,
+            spilled_count: Arc::new(AtomicU64::new(0)),
+            progress,
+            spill_progress,
+            capture,
+            lag,
+            on_demand,
+        })
+    }
+}
+
+/// The worker loop: block for one message, greedily drain more (up to
+/// `batch_size` or `max_latency`), then write the whole batch at once.
+fn run_worker(
+    rx: Receiver<Queued>,
+    mut sink: Sink,
+    batch_size: usize,
+    max_latency: Duration,
+    progress: &Progress,
+    capture: &Capture,
+    lag: &Lag,
+) {
+    let mut batch = Vec::with_capacity(batch_size);
+    let mut scratch = Vec::new();
+
+    let mut next = rx.recv();
+    while let Some(first) = next {
+        lag.set_oldest(Some(&first));
+        let started = Instant::now();
+        batch.push(first.msg);
+        while batch.len() < batch_size && started.elapsed() < max_latency {
+            match rx.try_recv() {
+                Some(queued) => batch.push(queued.msg),
+                None => break,
+            }
+        }
+
+        let written = batch.len() as u64;
+        if !capture.try_take(&mut batch) {
+            sink.write_batch(&mut batch, &mut scratch);
+        }
+        // Update the lag before `flush` callers can see the batch as done.
+        next = rx.try_recv();
+        lag.set_oldest(next.as_ref());
+        progress.record_processed(written);
+        if next.is_none() {
+            next = rx.recv();
+        }
+    }
+    sink.finish();
+}
+
+/// The spill loop: like `run_worker`, but fed by the unbounded overflow queue.
+/// Exits when the Logger (and so the queue's sender) is gone.
+fn run_spill_worker(
+    rx: mpsc::Receiver<Message>,
+    mut sink: Sink,
+    batch_size: usize,
+    progress: &Progress,
+) {
+    let mut batch = Vec::with_capacity(batch_size);
+    let mut scratch = Vec::new();
+
+    while let Ok(first) = rx.recv() {
+        batch.push(first);
+        while batch.len() < batch_size {
+            match rx.try_recv() {
+                Ok(msg) => batch.push(msg),
+                Err(_) => break,
+            }
+        }
+
+        let written = batch.len() as u64;
+        sink.write_batch(&mut batch, &mut scratch);
+        progress.record_processed(written);
+    }
+}
 
 /// A handle to the non-blocking logger
 /// This struct is cheap to clone and can be passed around the application
@@ -12,8 +688,15 @@ use std::thread;
 pub struct Logger {
     // FIX: Wrap Sender in Arc<Mutex<>> to safely allow multiple producers (MPSC behavior)
     // on top of the underlying SPSC channel.
-    sender: Arc<Mutex<Sender<String>>>,
+    producer: Arc<Mutex<Producer>>,
     dropped_count: Arc<AtomicU64>,
+    spilled_count: Arc<AtomicU64>,
+    progress: Arc<Progress>,
+    spill_progress: Arc<Progress>,
+    capture: Arc<Capture>,
+    lag: Arc<Lag>,
+    /// The writer to flush in `flush`, under [`FlushPolicy::OnDemand`].
+    on_demand: Option<SharedWriter>,
 }
 
 impl Logger {
@@ -22,30 +705,28 @@ impl Logger {
     /// # Arguments
     /// * `capacity` - The size of the ring buffer (messages). Must be power of 2.
     pub fn new(capacity: usize) -> Self {
-        let (tx, rx) = channel(capacity);
-        let dropped = Arc::new(AtomicU64::new(0));
-
-        // FIX: Removed unused variable `dropped_clone`
-
-        // Spawn the dedicated logging thread
-        thread::spawn(move || {
-            // BEST EFFORT: Try to pin to the very last core
-            // This is usually an efficient E-core or a core far from Core 0/1.
-            // We ignore the result (using `let _`) so this doesn't crash on macOS.
-            if let Some(last_core) = affinity::get_core_ids().last() {
-                let _ = affinity::pin_to_core(*last_core);
-            }
+        LoggerBuilder::new().capacity(capacity).build()
+    }
 
-            while let Some(msg) = rx.recv() {
-                println!("[LOG] {}", msg);
-            }
-        });
+    /// Creates a new Logger that hands every message to `sink` instead of stdout.
+    ///
+    /// The `sink` runs on the background worker thread, once per message, in the
+    /// order the messages were logged. This is useful for tests (capture into a
+    /// shared `Vec`) or for routing output somewhere other than the console.
+    ///
+    /// # Arguments
+    /// * `capacity` - The size of the ring buffer (messages). Must be power of 2.
+    /// * `sink` - Called on the worker thread for each message.
+    pub fn new_with_sink<F>(capacity: usize, sink: F) -> Self
+    where
+        F: Fn(String) + Send + 'static,
+    {
+        LoggerBuilder::new().capacity(capacity).sink(sink).build()
+    }
 
-        Self {
-            // Wrap the raw SPSC sender in a Mutex + Arc for thread-safe sharing
-            sender: Arc::new(Mutex::new(tx)),
-            dropped_count: dropped,
-        }
+    /// Returns a [`LoggerBuilder`] for configuring batching and output.
+    pub fn builder() -> LoggerBuilder {
+        LoggerBuilder::new()
     }
 
     /// Logs a message
@@ -53,12 +734,77 @@ impl Logger {
     /// This method is **Wait-Free** (mostly). It acquires a lightweight lock to ensure
     /// MPSC safety, then pushes to the queue.
     /// If the logging buffer is full, the message is silently dropped
-    /// and the internal `dropped_count` is incremented.
+    /// and the internal `dropped_count` is incremented (unless adaptive
+    /// backpressure has escalated to blocking, see [`LoggerBuilder::adaptive`],
+    /// or a [`LoggerBuilder::block_timeout`] is set).
     pub fn log(&self, msg: impl Into<String>) {
+        self.push(Message::from(msg.into()));
+    }
+
+    /// Logs a `format_args!` message without allocating a `String`.
+    ///
+    /// The message is formatted into a reusable per-thread arena and copied
+    /// into the ring slot, so in steady state this performs no heap
+    /// allocation as long as the result fits in [`INLINE_MESSAGE_LEN`] bytes.
+    /// Longer messages still work, but are carried as a `String`.
+    pub fn log_fmt(&self, args: fmt::Arguments<'_>) {
+        self.push(Message::format(args));
+    }
+
+    fn push(&self, msg: Message) {
         // FIX: Acquire the lock to safely access the SPSC sender
-        if let Ok(guard) = self.sender.lock() {
+        if let Ok(mut guard) = self.producer.lock() {
+            let producer = &mut *guard;
+            if producer.tx.is_closed() {
+                // Shut down: nobody is draining the queue any more.
+                self.dropped_count.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+            if let Backpressure::Adaptive(state) = &mut producer.backpressure {
+                state.roll_window(Instant::now());
+            }
+
+            let msg = Queued {
+                at: self.lag.now(),
+                msg,
+            };
             // We use `try_send` to ensure we NEVER block on the queue itself.
-            if guard.try_send(msg.into()).is_err() {
+            let mut msg = match producer.tx.try_send(msg) {
+                Ok(()) => {
+                    self.progress.enqueued.fetch_add(1, Ordering::Release);
+                    return;
+                }
+                Err(msg) => msg,
+            };
+
+            let block = match &mut producer.backpressure {
+                Backpressure::Adaptive(state) => state.on_full(),
+                Backpressure::Timeout(timeout) => match producer.tx.send_timeout(msg, *timeout) {
+                    Ok(()) => {
+                        self.progress.enqueued.fetch_add(1, Ordering::Release);
+                        return;
+                    }
+                    Err(returned) => {
+                        msg = returned;
+                        false
+                    }
+                },
+                Backpressure::Drop => false,
+            };
+
+            if block {
+                // Sustained pressure: losing data is now worse than waiting.
+                producer.tx.send(msg);
+                self.progress.enqueued.fetch_add(1, Ordering::Release);
+            } else if let Some(spill) = &producer.spill {
+                // Slow path: hand it to the spill thread instead of losing it.
+                if spill.send(msg.msg).is_ok() {
+                    self.spilled_count.fetch_add(1, Ordering::Relaxed);
+                    self.spill_progress.enqueued.fetch_add(1, Ordering::Release);
+                } else {
+                    self.dropped_count.fetch_add(1, Ordering::Relaxed);
+                }
+            } else {
                 // Drop the message to preserve latency
                 // Increment counter so we know we are losing data
                 self.dropped_count.fetch_add(1, Ordering::Relaxed);
@@ -66,6 +812,104 @@ impl Logger {
         }
     }
 
+    /// Blocks until every message logged *before* this call has been handed
+    /// to the sink by the worker thread (and any spilled ones to the spill sink).
+    ///
+    /// Under [`FlushPolicy::OnDemand`] it then flushes the writer sink.
+    ///
+    /// Dropped messages are not waited for. This is **not** meant for the hot
+    /// path; use it at shutdown or in tests.
+    pub fn flush(&self) {
+        self.progress.wait_caught_up();
+        self.spill_progress.wait_caught_up();
+        if let Some(out) = &self.on_demand {
+            let _ = out.lock().unwrap().flush();
+        }
+    }
+
+    /// Stops accepting messages and blocks until everything already logged
+    /// has been written. Affects every clone of this logger.
+    ///
+    /// Later `log` calls are counted as dropped. The worker threads exit
+    /// once drained.
+    pub fn shutdown(&self) {
+        self.shutdown_with_deadline(None);
+    }
+
+    /// Like [`shutdown`](Self::shutdown), but stops waiting after `timeout`
+    /// (e.g. when the sink is wedged on a full pipe).
+    ///
+    /// Returns how many messages were still undrained at that point. The
+    /// workers are detached and keep draining in the background, but the
+    /// caller is no longer held up by them.
+    pub fn shutdown_timeout(&self, timeout: Duration) -> u64 {
+        self.shutdown_with_deadline(Instant::now().checked_add(timeout))
+    }
+
+    fn shutdown_with_deadline(&self, deadline: Option<Instant>) -> u64 {
+        if let Ok(mut producer) = self.producer.lock() {
+            producer.tx.close();
+            // Dropping the spill sender lets the spill worker exit once drained.
+            producer.spill = None;
+        }
+        self.progress.wait_caught_up_until(deadline)
+            + self.spill_progress.wait_caught_up_until(deadline)
+    }
+
+    /// Runs `f` with the worker's output redirected to memory, and returns
+    /// `f`'s result along with every line logged meanwhile.
+    ///
+    /// For testing code that logs. Earlier messages are flushed to the real
+    /// sink first, and the call waits for the worker to process everything
+    /// logged during `f` before restoring it. Messages from other threads in
+    /// that window are captured too; spilled or dropped ones are not.
+    ///
+    /// # Panics
+    /// Panics if called from inside another `capture_scope` on this logger.
+    pub fn capture_scope<R>(&self, f: impl FnOnce() -> R) -> (R, Vec<String>) {
+        /// Turns capturing off again, even if `f` panics.
+        struct Restore<'a>(&'a Capture);
+        impl Drop for Restore<'_> {
+            fn drop(&mut self) {
+                self.0.active.store(false, Ordering::Release);
+            }
+        }
+
+        self.progress.wait_caught_up();
+        let was_active = self.capture.active.swap(true, Ordering::AcqRel);
+        assert!(!was_active, "capture_scope is not reentrant");
+        let restore = Restore(&self.capture);
+        // Leftovers from a scope whose closure panicked.
+        self.capture.lines.lock().unwrap().clear();
+
+        let result = f();
+        self.progress.wait_caught_up();
+        drop(restore);
+
+        let lines = std::mem::take(&mut *self.capture.lines.lock().unwrap());
+        (result, lines)
+    }
+
+    /// Returns how long the oldest message the worker hasn't finished
+    /// writing has been waiting, or `None` if it is caught up.
+    ///
+    /// A health signal for the logging backend: a lag that keeps growing
+    /// means the sink can't keep up. It is approximate (there is a brief
+    /// window after a message is queued before the worker notices it), and
+    /// only covers the main queue, not the spill path.
+    pub fn current_lag(&self) -> Option<Duration> {
+        match self.lag.oldest.load(Ordering::Acquire) {
+            0 => None,
+            at => Some(Duration::from_nanos(self.lag.now().saturating_sub(at - 1))),
+        }
+    }
+
+    /// Returns the number of messages sent to the spill sink
+    /// (see [`LoggerBuilder::spill`]).
+    pub fn get_spilled_count(&self) -> u64 {
+        self.spilled_count.load(Ordering::Relaxed)
+    }
+
     /// Returns the number of messages dropped due to a full buffer.
     pub fn get_dropped_count(&self) -> u64 {
         self.dropped_count.load(Ordering::Relaxed)
@@ -75,18 +919,25 @@ impl Logger {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::time::Duration;
 
     #[test]
     fn test_logger_basic() {
-        let logger = Logger::new(16);
+        let captured = Arc::new(Mutex::new(Vec::new()));
+        let sink_captured = captured.clone();
+        let logger = Logger::new_with_sink(16, move |msg| {
+            sink_captured.lock().unwrap().push(msg);
+        });
 
         logger.log("Hello World");
         logger.log("This is a test");
 
-        // Give the background thread a moment to print
-        thread::sleep(Duration::from_millis(50));
+        // Wait for the worker instead of sleeping and hoping.
+        logger.flush();
 
+        assert_eq!(
+            *captured.lock().unwrap(),
+            vec!["Hello World".to_string(), "This is a test".to_string()]
+        );
         assert_eq!(logger.get_dropped_count(), 0);
     }
 
@@ -107,4 +958,405 @@ mod tests {
         println!("Dropped {} messages (Expected > 0)", dropped);
         assert!(dropped > 0);
     }
+
+    #[test]
+    fn test_logger_on_chosen_core() {
+        let core = affinity::get_core_ids().first().copied();
+        let captured = Arc::new(Mutex::new(Vec::new()));
+        let sink_captured = captured.clone();
+        let logger = Logger::builder()
+            .capacity(16)
+            .worker_core(core)
+            .sink(move |msg| sink_captured.lock().unwrap().push(msg))
+            .build();
+
+        logger.log("pinned");
+        logger.flush();
+
+        assert_eq!(*captured.lock().unwrap(), vec!["pinned".to_string()]);
+    }
+
+    #[test]
+    fn test_adaptive_escalates_to_blocking() {
+        let captured = Arc::new(Mutex::new(Vec::new()));
+        let sink_captured = captured.clone();
+        let logger = Logger::builder()
+            .capacity(2)
+            .adaptive(5)
+            .sink(move |msg| {
+                // A slow sink, so the tiny buffer is always full.
+                thread::sleep(Duration::from_millis(1));
+                sink_captured.lock().unwrap().push(msg);
+            })
+            .build();
+
+        for i in 0..100 {
+            logger.log(format!("Flood {}", i));
+        }
+        logger.flush();
+
+        // At most `max_drops_per_sec` messages are lost before blocking kicks in.
+        let dropped = logger.get_dropped_count();
+        assert!(dropped <= 5, "dropped {} messages", dropped);
+        assert_eq!(captured.lock().unwrap().len() as u64, 100 - dropped);
+        // Everything after the escalation made it through.
+        assert_eq!(captured.lock().unwrap().last().unwrap(), "Flood 99");
+    }
+
+    #[test]
+    fn test_block_timeout_drops_only_past_deadline() {
+        // Moderate: the worker frees a slot well within the deadline.
+        let captured = Arc::new(Mutex::new(Vec::new()));
+        let sink_captured = captured.clone();
+        let logger = Logger::builder()
+            .capacity(4)
+            .block_timeout(Duration::from_secs(1))
+            .sink(move |msg| {
+                thread::sleep(Duration::from_millis(1));
+                sink_captured.lock().unwrap().push(msg);
+            })
+            .build();
+        for i in 0..50 {
+            logger.log(format!("Msg {}", i));
+        }
+        logger.flush();
+        assert_eq!(logger.get_dropped_count(), 0);
+        assert_eq!(captured.lock().unwrap().len(), 50);
+
+        // Extreme: the sink is stalled for the whole burst, so waits expire.
+        // With `batch_size(1)` the worker holds at most one message besides
+        // the 4 queued, so at least 25 of the 30 must be dropped.
+        let gate = Arc::new(Mutex::new(()));
+        let stalled = gate.lock().unwrap();
+        let captured = Arc::new(Mutex::new(Vec::new()));
+        let (sink_gate, sink_captured) = (gate.clone(), captured.clone());
+        let logger = Logger::builder()
+            .capacity(4)
+            .batch_size(1)
+            .block_timeout(Duration::from_millis(1))
+            .sink(move |msg| {
+                drop(sink_gate.lock().unwrap());
+                sink_captured.lock().unwrap().push(msg);
+            })
+            .build();
+        for i in 0..30 {
+            logger.log(format!("Msg {}", i));
+        }
+        drop(stalled);
+        logger.flush();
+        let (dropped, written) = (logger.get_dropped_count(), captured.lock().unwrap().len());
+        assert!(
+            dropped >= 25,
+            "expected drops past the deadline, got {dropped}"
+        );
+        assert_eq!(dropped + written as u64, 30);
+    }
+
+    #[test]
+    fn test_capture_scope() {
+        let captured = Arc::new(Mutex::new(Vec::new()));
+        let sink_captured = captured.clone();
+        let logger = Logger::new_with_sink(16, move |msg| {
+            sink_captured.lock().unwrap().push(msg);
+        });
+
+        logger.log("before");
+        let (answer, lines) = logger.capture_scope(|| {
+            for i in 0..3 {
+                logger.log_fmt(format_args!("step {}", i));
+            }
+            42
+        });
+        logger.log("after");
+        logger.flush();
+
+        assert_eq!(answer, 42);
+        assert_eq!(lines, vec!["step 0", "step 1", "step 2"]);
+        // The real sink saw everything else, and nothing from the scope.
+        assert_eq!(*captured.lock().unwrap(), vec!["before", "after"]);
+    }
+
+    #[test]
+    fn test_current_lag_grows_while_sink_is_stuck() {
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        let release_rx = Mutex::new(release_rx);
+        let logger = Logger::builder()
+            .sink(move |_| {
+                // Paused until the test says go.
+                let _ = release_rx.lock().unwrap().recv();
+            })
+            .build();
+        assert_eq!(logger.current_lag(), None);
+
+        logger.log("stuck");
+        logger.log("queued behind it");
+        let mut lag = logger.current_lag();
+        while lag.is_none() {
+            thread::yield_now();
+            lag = logger.current_lag();
+        }
+        thread::sleep(Duration::from_millis(20));
+        let later = logger.current_lag().unwrap();
+        assert!(later >= lag.unwrap() + Duration::from_millis(20));
+
+        drop(release_tx);
+        logger.flush();
+        assert_eq!(logger.current_lag(), None);
+    }
+
+    #[test]
+    fn test_adaptive_window_deescalates() {
+        let mut state = AdaptiveState::new(2);
+        let start = state.window_start;
+
+        assert!(!state.on_full());
+        assert!(!state.on_full());
+        assert!(state.on_full()); // 3 > 2: escalate
+
+        // The window that escalated still counts as pressure.
+        state.roll_window(start + AdaptiveState::WINDOW);
+        assert!(state.blocking);
+
+        // A quiet window brings us back to dropping.
+        state.roll_window(start + AdaptiveState::WINDOW * 2);
+        assert!(!state.blocking);
+        assert!(!state.on_full());
+    }
+
+    #[test]
+    fn test_log_target_routing() {
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+
+        LogTarget::Stdout
+            .emit(b"to out\n", &mut stdout, &mut stderr)
+            .unwrap();
+        assert_eq!(stdout, b"to out\n");
+        assert!(stderr.is_empty());
+
+        LogTarget::Stderr
+            .emit(b"to err\n", &mut stdout, &mut stderr)
+            .unwrap();
+        assert_eq!(stdout, b"to out\n");
+        assert_eq!(stderr, b"to err\n");
+
+        // And end to end through the worker (output goes to the real stderr).
+        let logger = Logger::builder().target(LogTarget::Stderr).build();
+        logger.log("stderr logger works");
+        logger.flush();
+        assert_eq!(logger.get_dropped_count(), 0);
+    }
+
+    /// A slow writer that records every byte and counts `write` calls.
+    struct CountingWriter {
+        data: Arc<Mutex<Vec<u8>>>,
+        writes: Arc<AtomicU64>,
+    }
+
+    impl Write for CountingWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            // Simulate slow I/O so messages pile up behind the worker.
+            thread::sleep(Duration::from_millis(5));
+            self.writes.fetch_add(1, Ordering::Relaxed);
+            self.data.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Counts `write` and `flush` calls.
+    struct FlushCounter {
+        writes: Arc<AtomicU64>,
+        flushes: Arc<AtomicU64>,
+    }
+
+    impl Write for FlushCounter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.writes.fetch_add(1, Ordering::Relaxed);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.flushes.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_named_worker_thread() {
+        let captured = Arc::new(Mutex::new(Vec::new()));
+        let sink_captured = captured.clone();
+        let logger = LoggerBuilder::new()
+            .thread_name("test-logger")
+            .stack_size(256 * 1024)
+            .sink(move |msg| {
+                let name = thread::current().name().map(str::to_string);
+                sink_captured.lock().unwrap().push((name, msg));
+            })
+            .try_build()
+            .unwrap();
+
+        logger.log("hello");
+        logger.flush();
+        assert_eq!(
+            *captured.lock().unwrap(),
+            vec![(Some("test-logger".to_string()), "hello".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_shutdown_timeout_with_slow_sink() {
+        let logger = LoggerBuilder::new()
+            .capacity(32)
+            .batch_size(1)
+            .sink(|_| thread::sleep(Duration::from_millis(50)))
+            .build();
+        for i in 0..20 {
+            logger.log(format!("msg {}", i));
+        }
+
+        let started = Instant::now();
+        let undrained = logger.shutdown_timeout(Duration::from_millis(100));
+        let elapsed = started.elapsed();
+
+        // Draining everything would take ~1s.
+        assert!(elapsed < Duration::from_millis(500), "took {:?}", elapsed);
+        assert!((1..20).contains(&undrained), "undrained {}", undrained);
+
+        // Closed: further messages are dropped, not queued.
+        logger.log("too late");
+        assert_eq!(logger.get_dropped_count(), 1);
+    }
+
+    #[test]
+    fn test_shutdown_drains_everything() {
+        let captured = Arc::new(Mutex::new(Vec::new()));
+        let sink_captured = captured.clone();
+        let logger = Logger::new_with_sink(16, move |msg| {
+            sink_captured.lock().unwrap().push(msg);
+        });
+        for i in 0..10 {
+            logger.log(format!("msg {}", i));
+        }
+        logger.shutdown();
+        assert_eq!(captured.lock().unwrap().len(), 10);
+    }
+
+    #[test]
+    fn test_flush_policies() {
+        // Logs 10 messages and returns (writes, flushes) once the worker has
+        // caught up but before `Logger::flush`, plus flushes after it.
+        fn run(policy: FlushPolicy) -> (u64, u64, u64) {
+            let writes = Arc::new(AtomicU64::new(0));
+            let flushes = Arc::new(AtomicU64::new(0));
+            let logger = LoggerBuilder::new()
+                .capacity(16)
+                .writer(FlushCounter {
+                    writes: writes.clone(),
+                    flushes: flushes.clone(),
+                })
+                .flush_policy(policy)
+                .build();
+            for i in 0..10 {
+                logger.log(format!("msg {}", i));
+            }
+            logger.progress.wait_caught_up();
+            let before = flushes.load(Ordering::Relaxed);
+            logger.flush();
+            (
+                writes.load(Ordering::Relaxed),
+                before,
+                flushes.load(Ordering::Relaxed),
+            )
+        }
+
+        assert_eq!(run(FlushPolicy::EveryMessage), (10, 10, 10));
+
+        let (writes, before, after) = run(FlushPolicy::EveryBatch);
+        assert_eq!(before, writes, "one flush per batch write");
+        assert_eq!(after, before);
+
+        let (writes, before, after) = run(FlushPolicy::OnDemand);
+        assert!(writes > 0);
+        assert_eq!((before, after), (0, 1));
+    }
+
+    /// Logs 100 messages through a counting writer; returns (writes, output).
+    fn count_writes(batch_size: usize) -> (u64, String) {
+        let data = Arc::new(Mutex::new(Vec::new()));
+        let writes = Arc::new(AtomicU64::new(0));
+        let logger = LoggerBuilder::new()
+            .capacity(128)
+            .batch_size(batch_size)
+            .max_batch_latency(Duration::from_secs(1))
+            .writer(CountingWriter {
+                data: data.clone(),
+                writes: writes.clone(),
+            })
+            .build();
+
+        for i in 0..100 {
+            logger.log(format!("msg {}", i));
+        }
+        logger.flush();
+        assert_eq!(logger.get_dropped_count(), 0);
+
+        let output = String::from_utf8(data.lock().unwrap().clone()).unwrap();
+        (writes.load(Ordering::Relaxed), output)
+    }
+
+    #[test]
+    fn test_spill_sink_captures_overflow() {
+        let captured = Arc::new(Mutex::new(Vec::new()));
+        let sink_captured = captured.clone();
+        let spilled = Arc::new(Mutex::new(Vec::new()));
+        let logger = LoggerBuilder::new()
+            .capacity(2)
+            .sink(move |msg| {
+                thread::sleep(Duration::from_millis(1));
+                sink_captured.lock().unwrap().push(msg);
+            })
+            .spill(CountingWriter {
+                data: spilled.clone(),
+                writes: Arc::new(AtomicU64::new(0)),
+            })
+            .build();
+
+        for i in 0..50 {
+            logger.log(format!("msg {}", i));
+        }
+        logger.flush();
+
+        let main = captured.lock().unwrap().len() as u64;
+        let spill_output = String::from_utf8(spilled.lock().unwrap().clone()).unwrap();
+        let spill = spill_output.lines().count() as u64;
+
+        // Nothing lost: every message went to exactly one of the two sinks.
+        assert_eq!(logger.get_dropped_count(), 0);
+        assert!(main > 0 && spill > 0, "main {}, spill {}", main, spill);
+        assert_eq!(spill, logger.get_spilled_count());
+        assert_eq!(main + spill, 50);
+    }
+
+    #[test]
+    fn test_batching_reduces_writes() {
+        let (unbatched, unbatched_output) = count_writes(1);
+        let (batched, batched_output) = count_writes(64);
+
+        // With batching disabled every message is its own write.
+        assert_eq!(unbatched, 100);
+        assert!(
+            batched < unbatched / 10,
+            "expected far fewer writes with batching, got {}",
+            batched
+        );
+
+        // Batching must not change what ends up in the sink.
+        let expected: String = (0..100).map(|i| format!("msg {}\n", i)).collect();
+        assert_eq!(batched_output, expected);
+        assert_eq!(unbatched_output, expected);
+    }
 }
@@ -0,0 +1,318 @@
+use super::{
+    DEFAULT_BATCH_SIZE, DEFAULT_MAX_BATCH_LATENCY, LogTarget, Message, Pinning, Progress, Sink,
+};
+use crate::channel::{Receiver, Sender, channel};
+use crate::error::Error;
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::thread;
+
+/// Hands out a unique id per logger, so a thread can hold lanes to several.
+static NEXT_LOGGER_ID: AtomicU64 = AtomicU64::new(0);
+
+/// A thread's lane to one logger: the logger's id, its registry (to tell
+/// when it is gone), and the sending end.
+type Lane = (u64, Weak<Registry>, Sender<Message>);
+
+thread_local! {
+    /// This thread's lanes, keyed by logger id. Dropped (closing every lane)
+    /// when the thread exits. The `Weak` tells when the logger and its
+    /// worker are gone, so the lane can be dropped earlier.
+    static LANES: RefCell<Vec<Lane>> = const { RefCell::new(Vec::new()) };
+}
+
+/// State shared between the handles and the worker.
+struct Registry {
+    id: u64,
+    lane_capacity: usize,
+    /// Every live lane. Producers push here once; the worker scans it.
+    lanes: Mutex<Vec<Receiver<Message>>>,
+    dropped_count: AtomicU64,
+    progress: Progress,
+    shutdown: AtomicBool,
+}
+
+/// Owned by every `ThreadLocalLogger` clone; tells the worker to stop once
+/// the last one is gone.
+struct Handle {
+    registry: Arc<Registry>,
+}
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        self.registry.shutdown.store(true, Ordering::Release);
+    }
+}
+
+/// A logger where each producer thread gets its own SPSC lane.
+///
+/// Unlike [`Logger`](super::Logger), which funnels every thread through one
+/// `Mutex`-guarded sender, a thread's first `log` call registers a private
+/// lane (kept in a thread-local), and every later call pushes to it without
+/// any locking. When the thread exits its lane is closed, and the worker
+/// drains and then forgets it.
+///
+/// The worker can't park on many lanes at once, so it polls them, sleeping
+/// [`DEFAULT_MAX_BATCH_LATENCY`] whenever all of them are empty.
+#[derive(Clone)]
+pub struct ThreadLocalLogger {
+    handle: Arc<Handle>,
+}
+
+impl ThreadLocalLogger {
+    /// Creates a logger that prints to stdout, giving each producer thread a
+    /// lane of `lane_capacity` messages.
+    pub fn new(lane_capacity: usize) -> Self {
+        Self::spawn(lane_capacity, Sink::Console(LogTarget::Stdout))
+    }
+
+    /// Like [`ThreadLocalLogger::new`], but hands every message to `sink` on
+    /// the worker thread.
+    pub fn new_with_sink<F>(lane_capacity: usize, sink: F) -> Self
+    where
+        F: Fn(String) + Send + 'static,
+    {
+        Self::spawn(lane_capacity, Sink::Callback(Box::new(sink)))
+    }
+
+    fn spawn(lane_capacity: usize, sink: Sink) -> Self {
+        let registry = Arc::new(Registry {
+            id: NEXT_LOGGER_ID.fetch_add(1, Ordering::Relaxed),
+            lane_capacity,
+            lanes: Mutex::new(Vec::new()),
+            dropped_count: AtomicU64::new(0),
+            progress: Progress::new(),
+            shutdown: AtomicBool::new(false),
+        });
+
+        let worker_registry = registry.clone();
+        thread::spawn(move || {
+            Pinning::LastCore.apply();
+            run_worker(&worker_registry, sink);
+        });
+
+        Self {
+            handle: Arc::new(Handle { registry }),
+        }
+    }
+
+    /// Logs a message through the calling thread's lane, creating and
+    /// registering the lane on first use.
+    ///
+    /// Never blocks after registration: if the lane is full the message is
+    /// dropped and counted, exactly like [`Logger::log`](super::Logger::log).
+    pub fn log(&self, msg: impl Into<String>) {
+        let registry = &self.handle.registry;
+        LANES.with(|lanes| {
+            let mut lanes = lanes.borrow_mut();
+            let lane = match lanes.iter().position(|(id, _, _)| *id == registry.id) {
+                Some(i) => i,
+                None => {
+                    // Forget lanes to loggers that no longer exist.
+                    lanes.retain(|(_, owner, _)| owner.strong_count() > 0);
+                    let (tx, rx) = channel(registry.lane_capacity);
+                    registry.lanes.lock().unwrap().push(rx);
+                    lanes.push((registry.id, Arc::downgrade(registry), tx));
+                    lanes.len() - 1
+                }
+            };
+
+            if lanes[lane].2.try_send(Message::from(msg.into())).is_ok() {
+                registry.progress.enqueued.fetch_add(1, Ordering::Release);
+            } else {
+                registry.dropped_count.fetch_add(1, Ordering::Relaxed);
+            }
+        });
+    }
+
+    /// Blocks until every message logged *before* this call (from any
+    /// thread) has been handed to the sink.
+    pub fn flush(&self) {
+        self.handle.registry.progress.wait_caught_up();
+    }
+
+    /// Returns the number of messages dropped due to a full lane.
+    pub fn get_dropped_count(&self) -> u64 {
+        self.handle.registry.dropped_count.load(Ordering::Relaxed)
+    }
+}
+
+/// Scans every lane, writes what it found, and sleeps when there was nothing.
+/// Lanes whose thread has exited are dropped once drained.
+fn run_worker(registry: &Registry, mut sink: Sink) {
+    let mut batch = Vec::new();
+    let mut scratch = Vec::new();
+    let mut next_lane = 0;
+
+    loop {
+        // Read the flag *before* scanning, so nothing logged before shutdown is missed.
+        let shutting_down = registry.shutdown.load(Ordering::Acquire);
+
+        collect_round_robin(
+            &mut registry.lanes.lock().unwrap(),
+            &mut next_lane,
+            &mut batch,
+        );
+
+        if !batch.is_empty() {
+            let written = batch.len() as u64;
+            sink.write_batch(&mut batch, &mut scratch);
+            registry.progress.record_processed(written);
+        } else if shutting_down {
+            return;
+        } else {
+            thread::sleep(DEFAULT_MAX_BATCH_LATENCY);
+        }
+    }
+}
+
+/// Moves up to [`DEFAULT_BATCH_SIZE`] messages into `batch`, one per lane per
+/// round, so a lane that is always full can't starve the others. Rounds
+/// start at `*next_lane`, which is left just past the last lane served.
+/// Lanes that are closed and drained are removed.
+fn collect_round_robin(
+    lanes: &mut Vec<Receiver<Message>>,
+    next_lane: &mut usize,
+    batch: &mut Vec<Message>,
+) {
+    let mut retired = Vec::new();
+    let mut last_served = None;
+    'rounds: loop {
+        let mut took_any = false;
+        for i in 0..lanes.len() {
+            if batch.len() >= DEFAULT_BATCH_SIZE {
+                break 'rounds;
+            }
+            let lane = (*next_lane + i) % lanes.len();
+            if retired.contains(&lane) {
+                continue;
+            }
+            match lanes[lane].try_recv_err() {
+                Ok(msg) => {
+                    batch.push(msg);
+                    last_served = Some(lane);
+                    took_any = true;
+                }
+                Err(Error::Empty) => {}
+                Err(_) => retired.push(lane),
+            }
+        }
+        if !took_any {
+            break;
+        }
+    }
+    if let Some(lane) = last_served {
+        *next_lane = lane + 1;
+    }
+
+    let mut index = 0;
+    lanes.retain(|_| {
+        index += 1;
+        !retired.contains(&(index - 1))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_all_threads_messages_arrive() {
+        let captured = Arc::new(Mutex::new(Vec::new()));
+        let sink_captured = captured.clone();
+        let logger = ThreadLocalLogger::new_with_sink(64, move |msg| {
+            sink_captured.lock().unwrap().push(msg);
+        });
+
+        let handles: Vec<_> = (0..4)
+            .map(|t| {
+                let logger = logger.clone();
+                thread::spawn(move || {
+                    for i in 0..32 {
+                        logger.log(format!("{}-{}", t, i));
+                    }
+                })
+            })
+            .collect();
+        for h in handles {
+            h.join().unwrap();
+        }
+        logger.flush();
+
+        let got = captured.lock().unwrap().clone();
+        assert_eq!(logger.get_dropped_count(), 0);
+        assert_eq!(got.len(), 4 * 32);
+
+        // Per-thread order is preserved.
+        for t in 0..4 {
+            let lane: Vec<_> = got
+                .iter()
+                .filter(|m| m.starts_with(&format!("{}-", t)))
+                .collect();
+            let expected: Vec<_> = (0..32).map(|i| format!("{}-{}", t, i)).collect();
+            assert_eq!(lane, expected.iter().collect::<Vec<_>>());
+        }
+
+        // Exited threads' lanes are retired once drained.
+        while !logger.handle.registry.lanes.lock().unwrap().is_empty() {
+            thread::sleep(DEFAULT_MAX_BATCH_LATENCY);
+        }
+    }
+
+    #[test]
+    fn test_full_lane_does_not_starve_others() {
+        let (busy_tx, busy_rx) = channel::<Message>(DEFAULT_BATCH_SIZE);
+        let (quiet_tx, quiet_rx) = channel::<Message>(DEFAULT_BATCH_SIZE);
+        let mut lanes = vec![busy_rx, quiet_rx];
+        let mut next_lane = 0;
+        let mut batch = Vec::new();
+        let keep_full = || while busy_tx.try_send(Message::from("busy".to_owned())).is_ok() {};
+        let log_quiet = || assert!(quiet_tx.try_send(Message::from("quiet".to_owned())).is_ok());
+
+        keep_full();
+        for _ in 0..3 {
+            log_quiet();
+        }
+        collect_round_robin(&mut lanes, &mut next_lane, &mut batch);
+        let got: Vec<_> = batch.iter().map(Message::as_str).collect();
+        assert_eq!(got.len(), DEFAULT_BATCH_SIZE);
+        assert_eq!(
+            got[..6],
+            ["busy", "quiet", "busy", "quiet", "busy", "quiet"]
+        );
+
+        // The busy lane is topped up again, yet the next quiet message is
+        // still among the first served.
+        batch.clear();
+        keep_full();
+        log_quiet();
+        collect_round_robin(&mut lanes, &mut next_lane, &mut batch);
+        let first_quiet = batch.iter().position(|m| m.as_str() == "quiet");
+        assert!(matches!(first_quiet, Some(0 | 1)), "quiet lane was starved");
+    }
+
+    #[test]
+    fn test_lanes_to_dropped_loggers_are_forgotten() {
+        thread::spawn(|| {
+            let first = ThreadLocalLogger::new_with_sink(4, |_| {});
+            first.log("to the first logger");
+            let registry = Arc::downgrade(&first.handle.registry);
+            drop(first);
+            // The worker holds the registry until it has shut down.
+            while registry.strong_count() > 0 {
+                thread::sleep(DEFAULT_MAX_BATCH_LATENCY);
+            }
+
+            let second = ThreadLocalLogger::new_with_sink(4, |_| {});
+            second.log("to the second logger");
+            LANES.with(|lanes| {
+                let lanes = lanes.borrow();
+                assert_eq!(lanes.len(), 1);
+                assert_eq!(lanes[0].0, second.handle.registry.id);
+            });
+        })
+        .join()
+        .unwrap();
+    }
+}
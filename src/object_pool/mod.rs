@@ -1,12 +1,30 @@
 #![doc = include_str!("README.md")]
 
+mod static_pool;
+
+pub use static_pool::{StaticObjectPool, StaticPooled};
+
+use crate::error::Error;
+use crossbeam_utils::Backoff;
 use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The first sleep [`ObjectPool::get_backoff`] takes once spinning is exhausted.
+const MIN_BACKOFF_SLEEP: Duration = Duration::from_micros(10);
+
+/// The longest single sleep [`ObjectPool::get_backoff`] takes between retries.
+pub const MAX_BACKOFF_SLEEP: Duration = Duration::from_millis(1);
 
 /// The core Object Pool.
 /// This struct holds the "free list" of pre-allocated objects.
 struct PoolInner<T> {
     items: Mutex<Vec<T>>,
+    /// Objects currently held by guards. Only changed while `items` is
+    /// locked, so `reinit` sees a consistent picture.
+    in_use: AtomicUsize,
 }
 
 /// A thread-safe, pre-allocating object pool.
@@ -31,13 +49,39 @@ pub struct Pooled<'a, T> {
     // in our `Drop` impl.
     item: Option<T>,
     pool: &'a ObjectPool<T>,
+    /// Set if the object was created past the pool's original capacity,
+    /// with what to do with it on return.
+    overflow: Option<OverflowPolicy>,
+}
+
+/// What a [`FactoryPool`] does with an overflow object (one created on a
+/// miss, past the original capacity) when its guard is dropped.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Add it to the pool, which so grows to fit the peak demand (the default).
+    #[default]
+    Retain,
+    /// Drop it, so the pool settles back to its original capacity.
+    Discard,
+}
+
+/// Like [`Pooled`], but holds a clone of the pool instead of borrowing it.
+///
+/// Because it has no lifetime, it can be stored in long-lived or shared
+/// structures (e.g. behind a `Mutex` accessed from several threads). It is
+/// `Send` when `T: Send`, and `Sync` when `T: Send + Sync`.
+pub struct OwnedPooled<T> {
+    item: Option<T>,
+    pool: ObjectPool<T>,
 }
 
 impl<T> ObjectPool<T> {
     /// Creates a new `ObjectPool` with a fixed capacity.
     ///
     /// The `init` closure is called `capacity` times to create
-    /// the pre-allocated objects.
+    /// the pre-allocated objects. A `capacity` of 0 is allowed: `init` is
+    /// never called and `try_get` always returns `None` (until objects are
+    /// added with [`put_many`](Self::put_many)).
     pub fn new<F>(capacity: usize, mut init: F) -> Self
     where
         F: FnMut() -> T,
@@ -50,6 +94,20 @@ impl<T> ObjectPool<T> {
         Self {
             inner: Arc::new(PoolInner {
                 items: Mutex::new(items),
+                in_use: AtomicUsize::new(0),
+            }),
+        }
+    }
+
+    /// Creates a pool of `capacity` clones of `T::default()`.
+    pub fn with_default(capacity: usize) -> Self
+    where
+        T: Default + Clone,
+    {
+        Self {
+            inner: Arc::new(PoolInner {
+                items: Mutex::new(vec![T::default(); capacity]),
+                in_use: AtomicUsize::new(0),
             }),
         }
     }
@@ -59,20 +117,196 @@ impl<T> ObjectPool<T> {
     /// If the pool is empty (all objects are in use), this
     /// returns `None`.
     pub fn try_get(&'_ self) -> Option<Pooled<'_, T>> {
-        let item = self.inner.items.lock().unwrap().pop()?;
+        let item = self.take()?;
 
         Some(Pooled {
             item: Some(item),
             pool: self,
+            overflow: None,
         })
     }
 
+    /// Like [`try_get`](Self::try_get), but retries up to `max_spins` times
+    /// (with exponential backoff) before giving up.
+    ///
+    /// There is no condvar involved, so no syscall on the happy path. Use it
+    /// when objects are typically returned within microseconds; for longer
+    /// waits the backoff degrades to `yield_now`.
+    pub fn get_spin(&'_ self, max_spins: usize) -> Option<Pooled<'_, T>> {
+        let backoff = Backoff::new();
+        for _ in 0..max_spins {
+            if let Some(item) = self.try_get() {
+                return Some(item);
+            }
+            backoff.snooze();
+        }
+        self.try_get()
+    }
+
+    /// Like [`get_spin`](Self::get_spin), but bounded by time: retries
+    /// `try_get` until `max_duration` has passed, then returns `None`.
+    ///
+    /// It spins with exponential backoff first, then escalates to short
+    /// sleeps (doubling up to [`MAX_BACKOFF_SLEEP`]) so a longer wait
+    /// doesn't burn a core. Still no condvar: this smooths over brief,
+    /// bursty exhaustion rather than waiting for a slow holder.
+    pub fn get_backoff(&'_ self, max_duration: Duration) -> Option<Pooled<'_, T>> {
+        let deadline = Instant::now().checked_add(max_duration);
+        let backoff = Backoff::new();
+        let mut nap = MIN_BACKOFF_SLEEP;
+        loop {
+            if let Some(item) = self.try_get() {
+                return Some(item);
+            }
+            let now = Instant::now();
+            let remaining = match deadline {
+                Some(deadline) if now >= deadline => return None,
+                Some(deadline) => deadline - now,
+                None => Duration::MAX,
+            };
+            if !backoff.is_completed() {
+                backoff.snooze();
+            } else {
+                thread::sleep(nap.min(remaining));
+                nap = (nap * 2).min(MAX_BACKOFF_SLEEP);
+            }
+        }
+    }
+
+    /// Locks the free list and passes the idle objects to `f`.
+    ///
+    /// Useful at quiescent points (e.g. leak checks, health assertions). The
+    /// pool is locked for the duration of `f`, so keep it short and don't
+    /// call back into the pool from inside it.
+    pub fn inspect<R>(&self, f: impl FnOnce(&[T]) -> R) -> R {
+        f(&self.inner.items.lock().unwrap())
+    }
+
+    /// Locks the free list and runs `f` on every idle object in place.
+    ///
+    /// For periodic upkeep (e.g. keepalives on pooled connections). Objects
+    /// checked out right now are skipped; their holders look after them.
+    /// As with [`inspect`](Self::inspect), the pool is locked throughout, so
+    /// don't call back into it from `f`.
+    pub fn maintain(&self, mut f: impl FnMut(&mut T)) {
+        for item in self.inner.items.lock().unwrap().iter_mut() {
+            f(item);
+        }
+    }
+
+    /// Like [`try_get`](Self::try_get), but returns an [`OwnedPooled`] guard
+    /// that keeps the pool alive instead of borrowing it.
+    pub fn try_get_owned(&self) -> Option<OwnedPooled<T>> {
+        let item = self.take()?;
+
+        Some(OwnedPooled {
+            item: Some(item),
+            pool: self.clone(),
+        })
+    }
+
+    /// Like [`try_get`](Self::try_get), but returns [`Error::Empty`] instead of `None`.
+    pub fn try_get_err(&'_ self) -> Result<Pooled<'_, T>, Error> {
+        self.try_get().ok_or(Error::Empty)
+    }
+
     /// Returns an object to the pool.
     ///
     /// Note: This is called automatically by the `Pooled` guard.
     /// You should rarely need to call this directly.
     fn put(&self, item: T) {
-        self.inner.items.lock().unwrap().push(item);
+        let mut items = self.inner.items.lock().unwrap();
+        items.push(item);
+        self.inner.in_use.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    /// Drops a checked-out object instead of returning it.
+    fn discard(&self, item: T) {
+        let items = self.inner.items.lock().unwrap();
+        self.inner.in_use.fetch_sub(1, Ordering::Relaxed);
+        drop(items);
+        drop(item);
+    }
+
+    /// Pops an idle object, counting it as in use.
+    fn take(&self) -> Option<T> {
+        let mut items = self.inner.items.lock().unwrap();
+        let item = items.pop()?;
+        self.inner.in_use.fetch_add(1, Ordering::Relaxed);
+        Some(item)
+    }
+
+    /// Replaces every pooled object with a fresh one from `init`, e.g. after
+    /// a configuration reload.
+    ///
+    /// All objects must be checked in: fails with [`Error::InUse`] (leaving
+    /// the pool untouched) if any guard is still alive. Otherwise the idle
+    /// objects are dropped and `init` is called once per object. The pool
+    /// is locked meanwhile, so `init` must not use the pool.
+    pub fn reinit<F: FnMut() -> T>(&self, mut init: F) -> Result<(), Error> {
+        let mut items = self.inner.items.lock().unwrap();
+        if self.inner.in_use.load(Ordering::Relaxed) != 0 {
+            return Err(Error::InUse);
+        }
+        let fresh = (0..items.len()).map(|_| init()).collect();
+        let old = std::mem::replace(&mut *items, fresh);
+        drop(items);
+        // The old objects' destructors run outside the lock.
+        drop(old);
+        Ok(())
+    }
+
+    /// Takes every idle object out of the pool at once, e.g. to flush pooled
+    /// buffers to disk at shutdown.
+    ///
+    /// Checked-out objects are unaffected: their guards return them to the
+    /// (now smaller) pool as usual. Hand objects back with
+    /// [`put_many`](Self::put_many) to restore the pool.
+    pub fn drain_available(&self) -> Vec<T> {
+        std::mem::take(&mut *self.inner.items.lock().unwrap())
+    }
+
+    /// Returns many objects to the pool at once, taking the lock only once.
+    ///
+    /// Useful at batch boundaries, or to seed the pool with objects created
+    /// elsewhere.
+    pub fn put_many(&self, items: impl IntoIterator<Item = T>) {
+        // Collect first: a lazy iterator may itself return objects here
+        // (e.g. by dropping a guard), which would deadlock under our lock.
+        let items: Vec<T> = items.into_iter().collect();
+        self.inner.items.lock().unwrap().extend(items);
+    }
+
+    /// Releases a batch of guards, returning their objects under a single lock
+    /// instead of locking once per guard drop.
+    ///
+    /// Guards that belong to a *different* pool are dropped normally (and so
+    /// return to their own pool), after this pool's lock is released.
+    pub fn release_all<'a>(&self, guards: impl IntoIterator<Item = Pooled<'a, T>>)
+    where
+        T: 'a,
+    {
+        // Drain the iterator before locking: it may drop guards of this very
+        // pool (e.g. ones it filters out), and their `Drop` takes the lock.
+        let guards: Vec<_> = guards.into_iter().collect();
+        let mut foreign = Vec::new();
+        let mut items = self.inner.items.lock().unwrap();
+        for mut guard in guards {
+            if Arc::ptr_eq(&guard.pool.inner, &self.inner) {
+                // Taking the item leaves the guard's `Drop` with nothing to do.
+                let item = guard.item.take();
+                if guard.overflow != Some(OverflowPolicy::Discard) {
+                    items.extend(item);
+                }
+                self.inner.in_use.fetch_sub(1, Ordering::Relaxed);
+            } else {
+                foreign.push(guard);
+            }
+        }
+        drop(items);
+        // Their own `Drop` returns them to their pools. Doing that under our
+        // lock would nest two pool locks, which can deadlock on lock order.
+        drop(foreign);
     }
 
     /// Returns the number of objects *available* in the pool.
@@ -81,6 +315,99 @@ impl<T> ObjectPool<T> {
     }
 }
 
+/// An [`ObjectPool`] that creates objects on demand with a fallible factory.
+///
+/// Idle objects are reused first. When the pool is empty, `get_or_create`
+/// calls the factory and propagates its error. Newly created objects are
+/// returned to the pool like any other when their guard drops, so the pool
+/// grows to fit the peak demand, unless [`OverflowPolicy::Discard`] is set.
+pub struct FactoryPool<T, E> {
+    pool: ObjectPool<T>,
+    factory: Arc<dyn Fn() -> Result<T, E> + Send + Sync>,
+    overflow_policy: OverflowPolicy,
+}
+
+impl<T, E> Clone for FactoryPool<T, E> {
+    fn clone(&self) -> Self {
+        Self {
+            pool: self.pool.clone(),
+            factory: self.factory.clone(),
+            overflow_policy: self.overflow_policy,
+        }
+    }
+}
+
+impl<T, E> FactoryPool<T, E> {
+    /// Creates a pool pre-filled with `capacity` objects from `factory`.
+    ///
+    /// Returns the factory's first error if pre-filling fails. Use a
+    /// `capacity` of 0 to start empty and create everything on demand.
+    pub fn new<F>(capacity: usize, factory: F) -> Result<Self, E>
+    where
+        F: Fn() -> Result<T, E> + Send + Sync + 'static,
+    {
+        let mut items = Vec::with_capacity(capacity);
+        for _ in 0..capacity {
+            items.push(factory()?);
+        }
+
+        Ok(Self {
+            pool: ObjectPool {
+                inner: Arc::new(PoolInner {
+                    items: Mutex::new(items),
+                    in_use: AtomicUsize::new(0),
+                }),
+            },
+            factory: Arc::new(factory),
+            overflow_policy: OverflowPolicy::Retain,
+        })
+    }
+
+    /// Sets what happens to overflow objects when their guard drops.
+    pub fn overflow_policy(mut self, policy: OverflowPolicy) -> Self {
+        self.overflow_policy = policy;
+        self
+    }
+
+    /// Retrieves an idle object, or creates a new one if the pool is empty.
+    ///
+    /// The factory runs *outside* the pool's lock, so a slow factory (e.g.
+    /// opening a connection) doesn't block other threads returning objects.
+    pub fn get_or_create(&self) -> Result<Pooled<'_, T>, E> {
+        if let Some(pooled) = self.pool.try_get() {
+            return Ok(pooled);
+        }
+
+        let item = (self.factory)()?;
+        // Counted as in use like any other object; `put` adds it to the pool.
+        // Under the lock, so a concurrent `reinit` sees it.
+        {
+            let _items = self.pool.inner.items.lock().unwrap();
+            self.pool.inner.in_use.fetch_add(1, Ordering::Relaxed);
+        }
+        Ok(Pooled {
+            item: Some(item),
+            pool: &self.pool,
+            overflow: Some(self.overflow_policy),
+        })
+    }
+
+    /// Retrieves an idle object without ever calling the factory.
+    pub fn try_get(&'_ self) -> Option<Pooled<'_, T>> {
+        self.pool.try_get()
+    }
+
+    /// Returns the number of objects *available* in the pool.
+    pub fn available(&self) -> usize {
+        self.pool.available()
+    }
+
+    /// Returns the underlying pool.
+    pub fn pool(&self) -> &ObjectPool<T> {
+        &self.pool
+    }
+}
+
 // --- Pooled Guard Implementations ---
 
 impl<'a, T> Deref for Pooled<'a, T> {
@@ -100,8 +427,46 @@ impl<'a, T> DerefMut for Pooled<'a, T> {
     }
 }
 
+impl<T> Pooled<'_, T> {
+    /// Returns true if the object was created by
+    /// [`FactoryPool::get_or_create`] on a miss, past the pool's capacity.
+    ///
+    /// Once a retained overflow object is back in the pool it is pooled like
+    /// any other, so a later guard for it reports `false`.
+    pub fn is_overflow(&self) -> bool {
+        self.overflow.is_some()
+    }
+}
+
 impl<'a, T> Drop for Pooled<'a, T> {
-    /// When the guard goes out of scope, return the item to the pool.
+    /// When the guard goes out of scope, return the item to the pool
+    /// (or drop it, for an overflow object under [`OverflowPolicy::Discard`]).
+    fn drop(&mut self) {
+        if let Some(item) = self.item.take() {
+            if self.overflow == Some(OverflowPolicy::Discard) {
+                self.pool.discard(item);
+            } else {
+                self.pool.put(item);
+            }
+        }
+    }
+}
+
+impl<T> Deref for OwnedPooled<T> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        // `item` is `Some` until drop, exactly as in `Pooled`.
+        self.item.as_ref().unwrap()
+    }
+}
+
+impl<T> DerefMut for OwnedPooled<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.item.as_mut().unwrap()
+    }
+}
+
+impl<T> Drop for OwnedPooled<T> {
     fn drop(&mut self) {
         if let Some(item) = self.item.take() {
             self.pool.put(item);
@@ -129,6 +494,155 @@ mod tests {
         Order { id: 0, price: 0.0 }
     }
 
+    fn assert_send<T: Send>() {}
+    fn assert_sync<T: Sync>() {}
+
+    #[test]
+    fn test_owned_pooled_auto_traits() {
+        assert_send::<OwnedPooled<Order>>();
+        assert_sync::<OwnedPooled<Order>>();
+        // Send but not Sync items still give a Send guard.
+        assert_send::<OwnedPooled<std::cell::Cell<u8>>>();
+    }
+
+    #[test]
+    fn test_owned_pooled_in_shared_structure() {
+        let pool = ObjectPool::new(4, new_order);
+        let held = Arc::new(Mutex::new(Vec::new()));
+
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                let pool = pool.clone();
+                let held = held.clone();
+                thread::spawn(move || {
+                    let mut order = pool.try_get_owned().unwrap();
+                    order.id = i;
+                    held.lock().unwrap().push(order);
+                })
+            })
+            .collect();
+        for h in handles {
+            h.join().unwrap();
+        }
+
+        assert_eq!(pool.available(), 0);
+        held.lock().unwrap().clear();
+        assert_eq!(pool.available(), 4);
+    }
+
+    #[test]
+    fn test_get_spin_waits_for_return() {
+        let pool = ObjectPool::new(1, new_order);
+        assert!(pool.get_spin(0).is_some());
+
+        let held = pool.try_get_owned().unwrap();
+        assert!(pool.get_spin(10).is_none());
+
+        let returner = thread::spawn(move || {
+            thread::sleep(std::time::Duration::from_millis(5));
+            drop(held);
+        });
+        assert!(pool.get_spin(10_000_000).is_some());
+        returner.join().unwrap();
+    }
+
+    #[test]
+    fn test_get_backoff_within_budget() {
+        let pool = ObjectPool::new(1, new_order);
+        let held = pool.try_get_owned().unwrap();
+
+        let start = std::time::Instant::now();
+        assert!(pool.get_backoff(Duration::from_millis(5)).is_none());
+        assert!(start.elapsed() >= Duration::from_millis(5));
+
+        let returner = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            drop(held);
+        });
+        assert!(pool.get_backoff(Duration::from_secs(5)).is_some());
+        returner.join().unwrap();
+    }
+
+    #[test]
+    fn test_inspect_idle_objects() {
+        let pool = ObjectPool::new(3, new_order);
+        {
+            let mut a = pool.try_get().unwrap();
+            let mut b = pool.try_get().unwrap();
+            a.id = 7;
+            b.id = 7;
+            assert_eq!(pool.inspect(|idle| idle.len()), 1);
+        }
+
+        // Everything is checked back in; two of them were used.
+        let (count, used) =
+            pool.inspect(|idle| (idle.len(), idle.iter().filter(|o| o.id == 7).count()));
+        assert_eq!((count, used), (3, 2));
+    }
+
+    #[test]
+    fn test_maintain_touches_idle_objects() {
+        let pool = ObjectPool::new(3, new_order);
+        let mut held = pool.try_get().unwrap();
+        held.id = 100;
+
+        let mut visited = 0;
+        pool.maintain(|order| {
+            order.id += 1;
+            visited += 1;
+        });
+        assert_eq!(visited, 2);
+        assert_eq!(held.id, 100);
+
+        assert_eq!(pool.try_get().unwrap().id, 1);
+        drop(held);
+        pool.inspect(|idle| {
+            let mut ids: Vec<u64> = idle.iter().map(|o| o.id).collect();
+            ids.sort();
+            assert_eq!(ids, [1, 1, 100]);
+        });
+    }
+
+    #[test]
+    fn test_drain_available() {
+        let pool = ObjectPool::new(4, new_order);
+        let held = pool.try_get().unwrap();
+
+        let drained = pool.drain_available();
+        assert_eq!(drained.len(), 3);
+        assert_eq!(pool.available(), 0);
+        assert!(pool.try_get().is_none());
+
+        // The checked-out object still comes back.
+        drop(held);
+        assert_eq!(pool.available(), 1);
+        assert_eq!(pool.drain_available().len(), 1);
+        assert!(pool.drain_available().is_empty());
+    }
+
+    #[test]
+    fn test_zero_capacity() {
+        let mut calls = 0;
+        let pool = ObjectPool::new(0, || {
+            calls += 1;
+            new_order()
+        });
+        assert_eq!(calls, 0);
+        assert_eq!(pool.available(), 0);
+        assert!(pool.try_get().is_none());
+        assert_eq!(pool.try_get_err().err(), Some(Error::Empty));
+        assert!(pool.get_spin(3).is_none());
+
+        // Seeding makes it usable like any other pool.
+        pool.put_many([new_order()]);
+        assert!(pool.try_get().is_some());
+
+        // The growable pool creates on demand from zero.
+        let factory = FactoryPool::new(0, || Ok::<_, String>(new_order())).unwrap();
+        assert!(factory.get_or_create().unwrap().is_overflow());
+        assert_eq!(factory.available(), 1);
+    }
+
     #[test]
     fn test_get_and_put() {
         let pool = ObjectPool::new(2, new_order);
@@ -197,4 +711,136 @@ mod tests {
         // the pool should be full again.
         assert_eq!(pool.available(), 100);
     }
+
+    #[test]
+    fn test_get_or_create_on_miss() {
+        let pool = FactoryPool::new(0, || Ok::<_, String>(new_order())).unwrap();
+        assert_eq!(pool.available(), 0);
+        assert!(pool.try_get().is_none());
+
+        {
+            let mut order = pool.get_or_create().unwrap();
+            order.id = 7;
+            assert_eq!(pool.available(), 0);
+        }
+
+        // The created object was returned to the pool and is reused.
+        assert_eq!(pool.available(), 1);
+        assert_eq!(pool.get_or_create().unwrap().id, 7);
+    }
+
+    #[test]
+    fn test_discarded_overflow_shrinks_back() {
+        let pool = FactoryPool::new(2, || Ok::<_, String>(new_order()))
+            .unwrap()
+            .overflow_policy(OverflowPolicy::Discard);
+
+        let a = pool.get_or_create().unwrap();
+        let b = pool.get_or_create().unwrap();
+        let extra = pool.get_or_create().unwrap();
+        assert!(!a.is_overflow() && !b.is_overflow());
+        assert!(extra.is_overflow());
+
+        drop(extra);
+        assert_eq!(pool.available(), 0);
+        drop((a, b));
+        assert_eq!(pool.available(), 2);
+
+        // Batch release honours the policy too.
+        let guards: Vec<_> = (0..3).map(|_| pool.get_or_create().unwrap()).collect();
+        pool.pool().release_all(guards);
+        assert_eq!(pool.available(), 2);
+        // Nothing is left counted as checked out.
+        assert_eq!(pool.pool().reinit(new_order), Ok(()));
+    }
+
+    #[test]
+    fn test_get_or_create_propagates_error() {
+        let pool: FactoryPool<Order, String> =
+            FactoryPool::new(0, || Err("connection refused".to_string())).unwrap();
+
+        match pool.get_or_create() {
+            Err(e) => assert_eq!(e, "connection refused"),
+            Ok(_) => panic!("factory error was swallowed"),
+        }
+        assert_eq!(pool.available(), 0);
+
+        // Pre-filling surfaces the same error.
+        let prefilled = FactoryPool::<Order, _>::new(2, || Err::<Order, _>("boom"));
+        assert!(matches!(prefilled, Err("boom")));
+    }
+
+    #[test]
+    fn test_with_default() {
+        let pool = ObjectPool::<u64>::with_default(8);
+        assert_eq!(pool.available(), 8);
+        pool.inspect(|idle| assert!(idle.iter().all(|&v| v == 0)));
+    }
+
+    #[test]
+    fn test_put_many() {
+        let pool = ObjectPool::new(0, new_order);
+        assert_eq!(pool.available(), 0);
+
+        pool.put_many((0..50).map(|_| new_order()));
+        assert_eq!(pool.available(), 50);
+    }
+
+    #[test]
+    fn test_release_all() {
+        let pool = ObjectPool::new(10, new_order);
+        let guards: Vec<_> = (0..10).map(|_| pool.try_get().unwrap()).collect();
+        assert_eq!(pool.available(), 0);
+
+        pool.release_all(guards);
+        assert_eq!(pool.available(), 10);
+    }
+
+    #[test]
+    fn test_release_all_with_lazy_filter() {
+        let pool = ObjectPool::new(4, new_order);
+        let mut guards: Vec<_> = (0..4).map(|_| pool.try_get().unwrap()).collect();
+        for (i, guard) in guards.iter_mut().enumerate() {
+            guard.id = i as u64;
+        }
+
+        // The filter drops the odd guards mid-iteration, which returns them
+        // to the same pool: that must not deadlock against `release_all`.
+        pool.release_all(guards.into_iter().filter(|g| g.id % 2 == 0));
+        assert_eq!(pool.available(), 4);
+    }
+
+    #[test]
+    fn test_reinit_sees_factory_made_objects() {
+        let pool = FactoryPool::new(0, || Ok::<_, String>(1u32)).unwrap();
+        let made = pool.get_or_create().unwrap();
+        assert_eq!(pool.pool().reinit(|| 2), Err(Error::InUse));
+        drop(made);
+        pool.pool().reinit(|| 2).unwrap();
+        pool.pool().inspect(|idle| assert_eq!(idle, [2]));
+    }
+
+    #[test]
+    fn test_reinit_replaces_objects() {
+        let pool = ObjectPool::new(3, || 1u32);
+
+        {
+            let _held = pool.try_get().unwrap();
+            assert_eq!(pool.reinit(|| 2), Err(Error::InUse));
+        }
+        // The failed attempt left the old objects alone.
+        pool.inspect(|idle| assert_eq!(idle, [1, 1, 1]));
+
+        let mut next = 10;
+        pool.reinit(|| {
+            next += 1;
+            next
+        })
+        .unwrap();
+        assert_eq!(pool.available(), 3);
+        let guards: Vec<_> = (0..3).map(|_| pool.try_get().unwrap()).collect();
+        let mut values: Vec<u32> = guards.iter().map(|g| **g).collect();
+        values.sort();
+        assert_eq!(values, [11, 12, 13]);
+    }
 }
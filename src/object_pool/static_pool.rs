@@ -0,0 +1,169 @@
+use crate::error::Error;
+use std::cell::UnsafeCell;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// An object pool whose storage is an inline `[T; N]` array.
+///
+/// Unlike [`ObjectPool`](super::ObjectPool) there is no `Vec` and no `Mutex`:
+/// each slot has an "in use" flag, and `try_get` claims a free one with a
+/// compare-and-swap. Nothing is heap-allocated after construction (and
+/// nothing at all if the pool itself lives in a `static` or on the stack).
+///
+/// `try_get` scans the flags, so it is `O(N)` in the worst case. Keep `N`
+/// small (tens to hundreds of objects).
+pub struct StaticObjectPool<T, const N: usize> {
+    slots: [UnsafeCell<T>; N],
+    in_use: [AtomicBool; N],
+}
+
+// SAFETY: A slot is only accessed through the guard that claimed its flag,
+// so each `T` is reachable from one thread at a time: `T: Send` suffices.
+unsafe impl<T: Send, const N: usize> Sync for StaticObjectPool<T, N> {}
+
+/// Guard for an object borrowed from a [`StaticObjectPool`]. Dropping it
+/// marks the slot free again.
+pub struct StaticPooled<'a, T, const N: usize> {
+    pool: &'a StaticObjectPool<T, N>,
+    index: usize,
+    // Behaves like `&mut T` for auto traits (`Sync` needs `T: Sync`).
+    _marker: PhantomData<&'a mut T>,
+}
+
+impl<T, const N: usize> StaticObjectPool<T, N> {
+    /// Creates a pool of `N` objects, calling `init` once per slot.
+    pub fn new(mut init: impl FnMut() -> T) -> Self {
+        Self {
+            slots: std::array::from_fn(|_| UnsafeCell::new(init())),
+            in_use: std::array::from_fn(|_| AtomicBool::new(false)),
+        }
+    }
+
+    /// Retrieves an object from the pool, or `None` if all `N` are in use.
+    pub fn try_get(&self) -> Option<StaticPooled<'_, T, N>> {
+        for (index, flag) in self.in_use.iter().enumerate() {
+            // Cheap load first, so a busy slot doesn't cost a CAS.
+            if !flag.load(Ordering::Relaxed)
+                && flag
+                    .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+                    .is_ok()
+            {
+                return Some(StaticPooled {
+                    pool: self,
+                    index,
+                    _marker: PhantomData,
+                });
+            }
+        }
+        None
+    }
+
+    /// Like [`try_get`](Self::try_get), but returns [`Error::Empty`] instead of `None`.
+    pub fn try_get_err(&self) -> Result<StaticPooled<'_, T, N>, Error> {
+        self.try_get().ok_or(Error::Empty)
+    }
+
+    /// Returns the number of objects *available* in the pool.
+    pub fn available(&self) -> usize {
+        self.in_use
+            .iter()
+            .filter(|flag| !flag.load(Ordering::Relaxed))
+            .count()
+    }
+
+    /// Returns the total number of objects (`N`).
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+}
+
+impl<T: Default, const N: usize> Default for StaticObjectPool<T, N> {
+    fn default() -> Self {
+        Self::new(T::default)
+    }
+}
+
+impl<T, const N: usize> Deref for StaticPooled<'_, T, N> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        // SAFETY: We hold the slot's flag, so no other guard aliases it.
+        unsafe { &*self.pool.slots[self.index].get() }
+    }
+}
+
+impl<T, const N: usize> DerefMut for StaticPooled<'_, T, N> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: As in `deref`, and `&mut self` makes this borrow unique.
+        unsafe { &mut *self.pool.slots[self.index].get() }
+    }
+}
+
+impl<T, const N: usize> Drop for StaticPooled<'_, T, N> {
+    fn drop(&mut self) {
+        // Release publishes our writes to the next guard's Acquire.
+        self.pool.in_use[self.index].store(false, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_borrow_all_exhaust_release_reborrow() {
+        let pool: StaticObjectPool<u64, 4> = StaticObjectPool::default();
+
+        let mut guards: Vec<_> = (0..4).map(|_| pool.try_get().unwrap()).collect();
+        for (i, g) in guards.iter_mut().enumerate() {
+            **g = i as u64 + 1;
+        }
+        assert_eq!(pool.available(), 0);
+        assert!(pool.try_get().is_none());
+        assert_eq!(pool.try_get_err().err(), Some(Error::Empty));
+
+        // Releasing one slot makes exactly that object available again.
+        let third = guards.remove(2);
+        drop(third);
+        assert_eq!(pool.available(), 1);
+        assert_eq!(*pool.try_get().unwrap(), 3);
+
+        drop(guards);
+        assert_eq!(pool.available(), pool.capacity());
+        let again: Vec<_> = (0..4).map(|_| pool.try_get().unwrap()).collect();
+        let mut values: Vec<u64> = again.iter().map(|g| **g).collect();
+        values.sort();
+        assert_eq!(values, [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_concurrent_guards_are_exclusive() {
+        use std::sync::atomic::AtomicU64;
+
+        let pool: StaticObjectPool<u64, 2> = StaticObjectPool::default();
+        let ops = AtomicU64::new(0);
+        thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for _ in 0..1_000 {
+                        let Some(mut g) = pool.try_get() else {
+                            thread::yield_now();
+                            continue;
+                        };
+                        // Yield mid-update: a second holder of this slot
+                        // would lose increments.
+                        let before = *g;
+                        thread::yield_now();
+                        *g = before + 1;
+                        ops.fetch_add(1, Ordering::Relaxed);
+                    }
+                });
+            }
+        });
+
+        assert_eq!(pool.available(), 2);
+        let (a, b) = (pool.try_get().unwrap(), pool.try_get().unwrap());
+        assert_eq!(*a + *b, ops.load(Ordering::Relaxed));
+    }
+}
@@ -0,0 +1,194 @@
+use super::{Orderings, RingBuffer};
+use std::sync::atomic::Ordering;
+
+/// A run of reserved slots, filled with [`push`](Self::push) and published
+/// all at once by [`commit`](Self::commit).
+///
+/// Returned by [`RingBuffer::reserve_n`]. The consumer sees none of the
+/// items until `commit`, then all of them. Dropping the writer without
+/// committing drops the items pushed so far and publishes nothing.
+pub struct BatchWriter<'a, T, O: Orderings> {
+    rb: &'a RingBuffer<T, O>,
+    /// The `head` value at reservation: the first reserved slot.
+    head: usize,
+    /// The `tail` value seen at reservation (for the occupancy stats).
+    #[cfg(feature = "stats")]
+    tail: usize,
+    reserved: usize,
+    filled: usize,
+}
+
+impl<T, O: Orderings> RingBuffer<T, O> {
+    /// Reserves `n` slots for a multi-part record that must appear to the
+    /// consumer atomically.
+    ///
+    /// This is a *Producer* method. Returns `None` if fewer than `n` slots
+    /// are free right now. The reservation may span the end of the buffer;
+    /// that is handled for you. Don't `send` while the writer is alive: the
+    /// reserved slots are the ones `send` would use. If you do, `commit`
+    /// panics and the writer leaks its items rather than drop slots that
+    /// `send` has published.
+    pub fn reserve_n(&self, n: usize) -> Option<BatchWriter<'_, T, O>> {
+        let head = self.head.load(Ordering::Relaxed);
+        let tail = self.tail.load(O::OBSERVE);
+        if self.cap - head.wrapping_sub(tail) < n {
+            return None;
+        }
+        Some(BatchWriter {
+            rb: self,
+            head,
+            #[cfg(feature = "stats")]
+            tail,
+            reserved: n,
+            filled: 0,
+        })
+    }
+}
+
+impl<T, O: Orderings> BatchWriter<'_, T, O> {
+    /// Writes `item` into the next reserved slot.
+    ///
+    /// # Panics
+    /// Panics if all reserved slots are already filled.
+    pub fn push(&mut self, item: T) {
+        assert!(
+            self.filled < self.reserved,
+            "BatchWriter: all {} reserved slots are filled",
+            self.reserved
+        );
+        let slot_idx = self.head.wrapping_add(self.filled) & (self.rb.cap - 1);
+        // SAFETY: `reserve_n` checked that the slots from `head` to
+        // `head + reserved` are free, and the consumer won't read them
+        // until we publish `head`.
+        unsafe { (*self.rb.buffer[slot_idx].get()).write(item) };
+        self.filled += 1;
+    }
+
+    /// Returns how many slots were reserved.
+    pub fn reserved(&self) -> usize {
+        self.reserved
+    }
+
+    /// Returns how many slots have been filled so far.
+    pub fn filled(&self) -> usize {
+        self.filled
+    }
+
+    /// Publishes every reserved slot with a single `Release` store of `head`.
+    ///
+    /// # Panics
+    /// Panics if not every reserved slot has been filled (the partial
+    /// record is dropped, not published), or if the producer sent while the
+    /// batch was reserved.
+    pub fn commit(mut self) {
+        assert_eq!(
+            self.filled, self.reserved,
+            "BatchWriter: committed with only {} of {} slots filled",
+            self.filled, self.reserved
+        );
+        assert_eq!(
+            self.rb.head.load(Ordering::Relaxed),
+            self.head,
+            "BatchWriter: the producer sent while a batch was reserved"
+        );
+        let new_head = self.head.wrapping_add(self.reserved);
+        self.rb.publish_head(new_head);
+
+        #[cfg(feature = "stats")]
+        self.rb.record_occupancy(new_head.wrapping_sub(self.tail));
+
+        // The items now belong to the consumer; our `Drop` must leave them be.
+        self.filled = 0;
+    }
+}
+
+impl<T, O: Orderings> Drop for BatchWriter<'_, T, O> {
+    fn drop(&mut self) {
+        // A `send` since `reserve_n` published (some of) our slots as its
+        // own items. Leak ours rather than drop theirs.
+        if self.rb.head.load(Ordering::Relaxed) != self.head {
+            return;
+        }
+        for i in 0..self.filled {
+            let slot_idx = self.head.wrapping_add(i) & (self.rb.cap - 1);
+            // SAFETY: We initialized the first `filled` slots and never
+            // published them, so nobody else can see them.
+            unsafe { (*self.rb.buffer[slot_idx].get()).assume_init_drop() };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_reserve_across_wrap_appears_at_once() {
+        let rb = RingBuffer::new(4);
+        // Leave one item in slot 2, so the reservation covers slots 3, 0, 1.
+        for i in 0..2 {
+            rb.send(i).unwrap();
+            assert_eq!(rb.recv(), Some(i));
+        }
+        rb.send(10).unwrap();
+
+        // Only 3 free slots.
+        assert!(rb.reserve_n(4).is_none());
+        let mut batch = rb.reserve_n(3).unwrap();
+        batch.push(11);
+        batch.push(12);
+        batch.push(13);
+
+        // Nothing is visible before the commit...
+        assert_eq!(rb.recv(), Some(10));
+        assert_eq!(rb.recv(), None);
+        batch.commit();
+
+        // ...and all three are after it.
+        assert_eq!(rb.len(), 3);
+        assert_eq!(rb.recv(), Some(11));
+        assert_eq!(rb.recv(), Some(12));
+        assert_eq!(rb.recv(), Some(13));
+        assert_eq!(rb.recv(), None);
+    }
+
+    #[test]
+    fn test_uncommitted_batch_is_dropped() {
+        let item = Arc::new(());
+        let rb = RingBuffer::new(4);
+        {
+            let mut batch = rb.reserve_n(2).unwrap();
+            batch.push(item.clone());
+            assert_eq!(batch.filled(), 1);
+        }
+        assert_eq!(Arc::strong_count(&item), 1);
+        assert!(rb.is_empty());
+
+        // The slots are reusable afterwards.
+        rb.send(item.clone()).unwrap();
+        assert_eq!(rb.len(), 1);
+    }
+
+    #[test]
+    fn test_send_during_reservation_leaks_instead_of_double_dropping() {
+        let rb = RingBuffer::new(4);
+        let mut batch = rb.reserve_n(1).unwrap();
+        batch.push(Box::new(1));
+        // Publishes the slot the batch just wrote, as `send`'s own item.
+        rb.send(Box::new(2)).unwrap();
+        drop(batch);
+        assert_eq!(rb.recv(), Some(Box::new(2)));
+        assert_eq!(rb.recv(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "the producer sent while a batch was reserved")]
+    fn test_commit_after_send_panics() {
+        let rb = RingBuffer::new(4);
+        let mut batch = rb.reserve_n(1).unwrap();
+        batch.push(1);
+        rb.send(2).unwrap();
+        batch.commit();
+    }
+}
@@ -0,0 +1,251 @@
+use super::RingBuffer;
+use std::mem::{self, ManuallyDrop, MaybeUninit};
+use std::ptr;
+
+/// A unit of work carried by a [`CommandQueue`].
+pub trait Command: Send + 'static {
+    /// Runs the command, consuming it.
+    fn execute(self);
+}
+
+/// Inline storage. The alignment caps which commands can be stored inline.
+#[repr(C, align(16))]
+struct InlineBuf<const N: usize>([MaybeUninit<u8>; N]);
+
+/// A command stored in place, with a hand-rolled "vtable" for its type.
+struct InlineCommand<const N: usize> {
+    data: InlineBuf<N>,
+    execute: unsafe fn(*mut u8),
+    drop: unsafe fn(*mut u8),
+}
+
+// SAFETY: `data` holds a `C: Command`, and `Command: Send`.
+unsafe impl<const N: usize> Send for InlineCommand<N> {}
+
+impl<const N: usize> InlineCommand<N> {
+    fn execute(self) {
+        // `execute` moves the command out, so our `Drop` must not run.
+        let mut this = ManuallyDrop::new(self);
+        // SAFETY: `data` holds an initialized command of the type `execute`
+        // was instantiated for, and we never touch it again.
+        unsafe { (this.execute)(this.data.0.as_mut_ptr().cast()) }
+    }
+}
+
+impl<const N: usize> Drop for InlineCommand<N> {
+    fn drop(&mut self) {
+        // SAFETY: As in `execute`; an un-run command is dropped exactly once.
+        unsafe { (self.drop)(self.data.0.as_mut_ptr().cast()) }
+    }
+}
+
+/// Object-safe shim so a boxed command can be run by value.
+trait BoxedCommand: Send {
+    fn run(self: Box<Self>);
+}
+
+impl<C: Command> BoxedCommand for C {
+    fn run(self: Box<Self>) {
+        (*self).execute()
+    }
+}
+
+enum Slot<const N: usize> {
+    Inline(InlineCommand<N>),
+    Boxed(Box<dyn BoxedCommand>),
+}
+
+/// An SPSC queue of heterogeneous commands that avoids a heap allocation
+/// per command.
+///
+/// Commands up to `N` bytes (and 16-byte alignment) are stored inline in
+/// the ring slots. Larger ones fall back to a `Box`. Choose `N` to cover
+/// your common commands: every slot is at least `N` bytes.
+pub struct CommandQueue<const N: usize = 48> {
+    inner: RingBuffer<Slot<N>>,
+}
+
+impl<const N: usize> CommandQueue<N> {
+    /// Creates a queue with *at least* the given capacity
+    /// (rounded up to the next power of 2, like [`RingBuffer::new`]).
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: RingBuffer::new(capacity),
+        }
+    }
+
+    /// Returns true if commands of type `C` are stored without boxing.
+    pub const fn is_inline<C: Command>() -> bool {
+        mem::size_of::<C>() <= N && mem::align_of::<C>() <= mem::align_of::<InlineBuf<N>>()
+    }
+
+    /// Enqueues a command. Fails with `Err(cmd)` if the queue is full.
+    ///
+    /// This is the *Producer* method.
+    pub fn push<C: Command>(&self, cmd: C) -> Result<(), C> {
+        // Only the consumer can free space, so if there is room now, the
+        // `send` below cannot fail. Checking first lets us hand `cmd` back.
+        if self.inner.len() >= self.inner.capacity() {
+            return Err(cmd);
+        }
+
+        let slot = if Self::is_inline::<C>() {
+            let mut data = InlineBuf([MaybeUninit::uninit(); N]);
+            // SAFETY: `is_inline` checked that `C` fits and is suitably aligned.
+            unsafe { ptr::write(data.0.as_mut_ptr().cast::<C>(), cmd) };
+            Slot::Inline(InlineCommand {
+                data,
+                execute: execute_in_place::<C>,
+                drop: drop_in_place::<C>,
+            })
+        } else {
+            Slot::Boxed(Box::new(cmd))
+        };
+
+        if self.inner.send(slot).is_err() {
+            unreachable!("checked for room above");
+        }
+        Ok(())
+    }
+
+    /// Runs the oldest command. Returns `false` if the queue was empty.
+    ///
+    /// This is the *Consumer* method.
+    pub fn execute_next(&self) -> bool {
+        match self.inner.recv() {
+            Some(Slot::Inline(cmd)) => cmd.execute(),
+            Some(Slot::Boxed(cmd)) => cmd.run(),
+            None => return false,
+        }
+        true
+    }
+
+    /// Runs every queued command in order. Returns how many ran.
+    pub fn execute_all(&self) -> usize {
+        let mut ran = 0;
+        while self.execute_next() {
+            ran += 1;
+        }
+        ran
+    }
+
+    /// Returns the number of queued commands.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns true if no commands are queued.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+}
+
+/// # Safety
+/// `ptr` must point at an initialized `C` that is not used afterwards.
+unsafe fn execute_in_place<C: Command>(ptr: *mut u8) {
+    unsafe { ptr::read(ptr.cast::<C>()) }.execute()
+}
+
+/// # Safety
+/// `ptr` must point at an initialized `C` that is not used afterwards.
+unsafe fn drop_in_place<C>(ptr: *mut u8) {
+    unsafe { ptr::drop_in_place(ptr.cast::<C>()) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    type Log = Arc<Mutex<Vec<String>>>;
+
+    struct Small {
+        id: u8,
+        log: Log,
+    }
+
+    impl Command for Small {
+        fn execute(self) {
+            self.log.lock().unwrap().push(format!("small {}", self.id));
+        }
+    }
+
+    struct Large {
+        payload: [u64; 32],
+        log: Log,
+    }
+
+    impl Command for Large {
+        fn execute(self) {
+            let sum: u64 = self.payload.iter().sum();
+            self.log.lock().unwrap().push(format!("large {}", sum));
+        }
+    }
+
+    #[test]
+    fn test_mixed_commands_run_in_order() {
+        assert!(CommandQueue::<48>::is_inline::<Small>());
+        assert!(!CommandQueue::<48>::is_inline::<Large>());
+
+        let log: Log = Arc::default();
+        let queue = CommandQueue::<48>::new(4);
+
+        queue
+            .push(Small {
+                id: 1,
+                log: log.clone(),
+            })
+            .ok()
+            .unwrap();
+        queue
+            .push(Large {
+                payload: [1; 32],
+                log: log.clone(),
+            })
+            .ok()
+            .unwrap();
+        queue
+            .push(Small {
+                id: 2,
+                log: log.clone(),
+            })
+            .ok()
+            .unwrap();
+
+        assert_eq!(queue.execute_all(), 3);
+        assert_eq!(*log.lock().unwrap(), vec!["small 1", "large 32", "small 2"]);
+    }
+
+    #[test]
+    fn test_unexecuted_commands_are_dropped() {
+        let log: Log = Arc::default();
+        let queue = CommandQueue::<48>::new(2);
+        queue
+            .push(Small {
+                id: 1,
+                log: log.clone(),
+            })
+            .ok()
+            .unwrap();
+        queue
+            .push(Small {
+                id: 2,
+                log: log.clone(),
+            })
+            .ok()
+            .unwrap();
+        assert!(
+            queue
+                .push(Small {
+                    id: 3,
+                    log: log.clone()
+                })
+                .is_err()
+        );
+
+        drop(queue);
+        // Only our handle is left: the queued commands released theirs.
+        assert_eq!(Arc::strong_count(&log), 1);
+        assert!(log.lock().unwrap().is_empty());
+    }
+}
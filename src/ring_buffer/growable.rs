@@ -0,0 +1,106 @@
+use super::RingBuffer;
+
+/// A ring buffer that grows instead of failing when full.
+///
+/// `try_send` and `recv` are the ordinary lock-free operations, but `send`
+/// takes `&mut self`: when the buffer is full it allocates one twice as big
+/// and moves the live items across. That makes it a poor fit for a hot path,
+/// but handy for control channels where losing a message is worse than an
+/// occasional reallocation.
+pub struct GrowableRingBuffer<T> {
+    inner: RingBuffer<T>,
+}
+
+impl<T> GrowableRingBuffer<T> {
+    /// Creates a buffer with *at least* the given initial capacity
+    /// (rounded up to the next power of 2, like [`RingBuffer::new`]).
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: RingBuffer::new(capacity),
+        }
+    }
+
+    /// Sends an item, doubling the capacity first if the buffer is full.
+    ///
+    /// # Panics
+    /// Panics if the doubled buffer cannot be allocated.
+    pub fn send(&mut self, item: T) {
+        if let Err(item) = self.inner.send(item) {
+            self.grow();
+            // The new buffer has at least one free slot.
+            let _ = self.inner.send(item);
+        }
+    }
+
+    /// Sends an item without growing. Fails with `Err(item)` if full.
+    pub fn try_send(&self, item: T) -> Result<(), T> {
+        self.inner.send(item)
+    }
+
+    /// Receives the oldest item, or `None` if empty.
+    pub fn recv(&self) -> Option<T> {
+        self.inner.recv()
+    }
+
+    /// Returns the current capacity.
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+
+    /// Returns the number of items currently queued.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns true if the buffer is empty.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Doubles the capacity, preserving the order of the queued items.
+    fn grow(&mut self) {
+        let bigger = RingBuffer::new(self.inner.capacity() * 2);
+        while let Some(item) = self.inner.recv() {
+            // Can't fail: `bigger` has twice the room we are draining.
+            let _ = bigger.send(item);
+        }
+        self.inner = bigger;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grows_and_preserves_order() {
+        let mut rb = GrowableRingBuffer::new(4);
+        assert_eq!(rb.capacity(), 4);
+
+        // Rotate the indices first so the live items wrap around the end.
+        for i in 0..3 {
+            rb.send(i);
+            assert_eq!(rb.recv(), Some(i));
+        }
+
+        for i in 0..10 {
+            rb.send(i);
+        }
+        assert_eq!(rb.capacity(), 16);
+        assert_eq!(rb.len(), 10);
+
+        for i in 0..10 {
+            assert_eq!(rb.recv(), Some(i));
+        }
+        assert!(rb.is_empty());
+    }
+
+    #[test]
+    fn test_try_send_does_not_grow() {
+        let rb = GrowableRingBuffer::new(2);
+        rb.try_send(1).unwrap();
+        rb.try_send(2).unwrap();
+        assert_eq!(rb.try_send(3), Err(3));
+        assert_eq!(rb.capacity(), 2);
+    }
+}
@@ -0,0 +1,200 @@
+use super::{DefaultOrderings, Orderings, RingBuffer};
+use std::io::{self, Read, Write};
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+
+/// Creates a thread-to-thread byte pipe over a `RingBuffer<u8>` of (at
+/// least) `capacity` bytes.
+///
+/// Move the [`RingWriter`] to the producing thread and the [`RingReader`]
+/// to the consuming one.
+pub fn byte_pipe(capacity: usize) -> (RingWriter, RingReader) {
+    let rb = Arc::new(RingBuffer::new(capacity));
+    (RingWriter::new(rb.clone()), RingReader::new(rb))
+}
+
+/// The producing end of a byte pipe, implementing [`Write`].
+///
+/// `write` copies as many bytes as fit and publishes them with a single
+/// `Release` store. When the buffer is full it fails with
+/// [`io::ErrorKind::WouldBlock`] instead of blocking. Dropping the writer
+/// closes the stream, so the reader sees EOF once it has drained it.
+pub struct RingWriter<O: Orderings = DefaultOrderings> {
+    rb: Arc<RingBuffer<u8, O>>,
+}
+
+/// The consuming end of a byte pipe, implementing [`Read`].
+///
+/// `read` fails with [`io::ErrorKind::WouldBlock`] while the buffer is
+/// empty and the stream is open, and returns `Ok(0)` (EOF) once it is
+/// closed and drained.
+pub struct RingReader<O: Orderings = DefaultOrderings> {
+    rb: Arc<RingBuffer<u8, O>>,
+}
+
+impl<O: Orderings> RingWriter<O> {
+    /// Wraps the producer side of `rb`.
+    ///
+    /// The buffer must have no other producer, e.g. no second writer.
+    pub fn new(rb: Arc<RingBuffer<u8, O>>) -> Self {
+        Self { rb }
+    }
+
+    /// Ends the stream. Bytes already written can still be read.
+    pub fn close(&self) {
+        self.rb.close();
+    }
+}
+
+impl<O: Orderings> Write for RingWriter<O> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let rb = &*self.rb;
+        let head = rb.head.load(Ordering::Relaxed);
+        let tail = rb.tail.load(O::OBSERVE);
+        let n = buf.len().min(rb.cap - head.wrapping_sub(tail));
+        if n == 0 {
+            return Err(io::ErrorKind::WouldBlock.into());
+        }
+        for (i, &byte) in buf[..n].iter().enumerate() {
+            let slot_idx = head.wrapping_add(i) & (rb.cap - 1);
+            // SAFETY: The `n` slots from `head` are free (checked against
+            // `tail` above), and the consumer won't read them until we
+            // publish `head`.
+            unsafe { (*rb.buffer[slot_idx].get()).write(byte) };
+        }
+        let new_head = head.wrapping_add(n);
+        rb.publish_head(new_head);
+
+        #[cfg(feature = "stats")]
+        rb.record_occupancy(new_head.wrapping_sub(tail));
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        // Every successful `write` is already published.
+        Ok(())
+    }
+}
+
+impl<O: Orderings> Drop for RingWriter<O> {
+    fn drop(&mut self) {
+        self.rb.close();
+    }
+}
+
+impl<O: Orderings> RingReader<O> {
+    /// Wraps the consumer side of `rb`.
+    ///
+    /// The buffer must have no other consumer, e.g. no second reader.
+    pub fn new(rb: Arc<RingBuffer<u8, O>>) -> Self {
+        Self { rb }
+    }
+
+    /// Returns how many bytes can be read right now.
+    pub fn available(&self) -> usize {
+        self.rb.len()
+    }
+
+    fn read_available(&self, buf: &mut [u8]) -> usize {
+        let rb = &*self.rb;
+        let tail = rb.tail.load(Ordering::Relaxed);
+        let head = rb.head.load(O::OBSERVE);
+        let n = buf.len().min(head.wrapping_sub(tail));
+        if n == 0 {
+            return 0;
+        }
+        for (i, byte) in buf[..n].iter_mut().enumerate() {
+            let slot_idx = tail.wrapping_add(i) & (rb.cap - 1);
+            // SAFETY: `tail + i < head`, so as in `recv` the slot holds a
+            // published byte.
+            *byte = unsafe { (*rb.buffer[slot_idx].get()).assume_init_read() };
+        }
+        rb.publish_tail(tail.wrapping_add(n));
+
+        #[cfg(feature = "stats")]
+        rb.record_occupancy(head.wrapping_sub(tail).wrapping_sub(n));
+        n
+    }
+}
+
+impl<O: Orderings> Read for RingReader<O> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let n = self.read_available(buf);
+        if n > 0 {
+            return Ok(n);
+        }
+        if self.rb.is_closed() {
+            // As in `try_recv`: bytes written just before `close` are
+            // visible once we've seen the flag.
+            return Ok(self.read_available(buf));
+        }
+        Err(io::ErrorKind::WouldBlock.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_byte_pipe_across_threads() {
+        let data: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+        let (mut writer, mut reader) = byte_pipe(64);
+
+        let expected = data.clone();
+        let producer = thread::spawn(move || {
+            let mut rest = &data[..];
+            while !rest.is_empty() {
+                match writer.write(rest) {
+                    Ok(n) => rest = &rest[n..],
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => thread::yield_now(),
+                    Err(e) => panic!("unexpected error: {e}"),
+                }
+            }
+            // Dropping the writer closes the stream.
+        });
+
+        let mut received = Vec::new();
+        let mut chunk = [0u8; 37];
+        loop {
+            match reader.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(n) => received.extend_from_slice(&chunk[..n]),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => thread::yield_now(),
+                Err(e) => panic!("unexpected error: {e}"),
+            }
+        }
+        producer.join().unwrap();
+        assert_eq!(received, expected);
+    }
+
+    #[test]
+    fn test_full_empty_and_eof() {
+        let (mut writer, mut reader) = byte_pipe(4);
+        let mut buf = [0u8; 8];
+        assert_eq!(
+            reader.read(&mut buf).unwrap_err().kind(),
+            io::ErrorKind::WouldBlock
+        );
+
+        // A short write, then WouldBlock once full.
+        assert_eq!(writer.write(b"abcdef").unwrap(), 4);
+        assert_eq!(
+            writer.write(b"ef").unwrap_err().kind(),
+            io::ErrorKind::WouldBlock
+        );
+        assert_eq!(reader.available(), 4);
+
+        writer.close();
+        assert_eq!(reader.read(&mut buf).unwrap(), 4);
+        assert_eq!(&buf[..4], b"abcd");
+        assert_eq!(reader.read(&mut buf).unwrap(), 0);
+    }
+}
@@ -0,0 +1,321 @@
+use super::{RingBuffer, Slots};
+use crossbeam_utils::CachePadded;
+use std::fs::OpenOptions;
+use std::io;
+use std::mem;
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+use std::ptr::{self, NonNull};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Identifies a file created by `RingBuffer::from_mmap` ("LLTRING1").
+const MAGIC: u64 = u64::from_le_bytes(*b"LLTRING1");
+
+/// The fixed header at the start of the mapped file.
+///
+/// The counters are `u64` (not `usize`) so the layout doesn't depend on the
+/// pointer width of the process that wrote it.
+#[repr(C)]
+struct Header {
+    magic: u64,
+    capacity: u64,
+    slot_size: u64,
+    slot_align: u64,
+    head: CachePadded<AtomicU64>,
+    tail: CachePadded<AtomicU64>,
+}
+
+/// A shared, read-write mapping of a ring buffer file: the header, then the
+/// slots. Unmapped on drop.
+pub(super) struct MmapFile {
+    /// Start of the mapping (page aligned).
+    base: *mut u8,
+    /// Length of the mapping in bytes.
+    len: usize,
+}
+
+impl MmapFile {
+    fn header(&self) -> &Header {
+        // SAFETY: `base` points at an initialized `Header` for as long as we live.
+        unsafe { &*(self.base as *const Header) }
+    }
+
+    /// Records `head` in the file. `Release`, so the slots it covers are
+    /// written to the mapping before it.
+    pub(super) fn set_head(&self, head: usize) {
+        self.header().head.store(head as u64, Ordering::Release);
+    }
+
+    /// Records `tail` in the file.
+    pub(super) fn set_tail(&self, tail: usize) {
+        self.header().tail.store(tail as u64, Ordering::Release);
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        // SAFETY: `base`/`len` describe our live mapping.
+        if unsafe { libc::msync(self.base as *mut libc::c_void, self.len, libc::MS_SYNC) } == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+}
+
+impl Drop for MmapFile {
+    fn drop(&mut self) {
+        // SAFETY: `base`/`len` came from a successful `mmap`, and the
+        // `RingBuffer` that borrowed the slots is done with them.
+        unsafe {
+            libc::munmap(self.base as *mut libc::c_void, self.len);
+        }
+    }
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+impl<T: Copy> RingBuffer<T> {
+    /// Opens (or creates) a ring buffer whose slots and head/tail counters
+    /// live in the memory-mapped file at `path`, with *at least* `capacity`
+    /// slots (rounded up to the next power of 2).
+    ///
+    /// Everything queued survives the process: reopening the same file with
+    /// the same capacity and element type recovers the unread items. (It
+    /// survives a crash, not a power loss. Call
+    /// [`flush_mmap`](Self::flush_mmap) for that.) The result is an ordinary
+    /// `RingBuffer`, so it can also back a channel.
+    ///
+    /// A new file is sized and initialized as an empty queue. An existing
+    /// file must have been created with the same capacity and element
+    /// layout, and its counters must describe at most `capacity` queued
+    /// items, otherwise this fails with `InvalidData`.
+    ///
+    /// # Safety
+    /// Reopening reinterprets the file's bytes as `T`s, so:
+    /// - `T` must be plain data that is valid for any bit pattern (integers,
+    ///   floats, and arrays or `#[repr(C)]` structs of them). No references,
+    ///   pointers, `bool`, `char` or enums.
+    /// - The file must have been written by `from_mmap` with the same `T`
+    ///   (the header only records its size and alignment).
+    /// - Nothing else may map or modify the file while the buffer is alive,
+    ///   including a second `from_mmap` of the same path.
+    pub unsafe fn from_mmap(path: impl AsRef<Path>, capacity: usize) -> io::Result<Self> {
+        let cap = capacity.checked_next_power_of_two().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "capacity overflows usize")
+        })?;
+        let slots_offset = mem::size_of::<Header>().next_multiple_of(mem::align_of::<T>());
+        let len = cap
+            .checked_mul(mem::size_of::<T>())
+            .and_then(|bytes| bytes.checked_add(slots_offset))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "capacity too large"))?;
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        let existing = file.metadata()?.len();
+        let fresh = existing == 0;
+        if fresh {
+            // `set_len` zero-fills, so the counters start at 0.
+            file.set_len(len as u64)?;
+        } else if existing != len as u64 {
+            return Err(invalid_data(
+                "file size does not match capacity and element type",
+            ));
+        }
+
+        // SAFETY: We map exactly `len` bytes of a file we just sized to `len`.
+        // The mapping is released when `map` drops. The file can be closed
+        // afterwards.
+        let base = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if base == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        // From here on, dropping `map` unmaps, including on the error paths.
+        let map = MmapFile {
+            base: base as *mut u8,
+            len,
+        };
+
+        let expected = (
+            MAGIC,
+            cap as u64,
+            mem::size_of::<T>() as u64,
+            mem::align_of::<T>() as u64,
+        );
+        if fresh {
+            // SAFETY: The mapping is page aligned and at least `size_of::<Header>()`
+            // bytes long; nobody else has it yet.
+            unsafe {
+                ptr::write(
+                    map.base as *mut Header,
+                    Header {
+                        magic: expected.0,
+                        capacity: expected.1,
+                        slot_size: expected.2,
+                        slot_align: expected.3,
+                        head: CachePadded::new(AtomicU64::new(0)),
+                        tail: CachePadded::new(AtomicU64::new(0)),
+                    },
+                );
+            }
+        }
+        let h = map.header();
+        if (h.magic, h.capacity, h.slot_size, h.slot_align) != expected {
+            return Err(invalid_data(
+                "file was created for a different capacity or element type",
+            ));
+        }
+        // Counters are kept modulo `usize`, like the in-memory ones.
+        let head = h.head.load(Ordering::Acquire) as usize;
+        let tail = h.tail.load(Ordering::Acquire) as usize;
+        if head.wrapping_sub(tail) > cap {
+            return Err(invalid_data("head/tail counters are corrupt"));
+        }
+
+        // SAFETY: The mapping holds `cap` slots of `T`'s layout at
+        // `slots_offset` (a multiple of `align_of::<T>()` from a page
+        // aligned base), and `map` is stored next to them below, so they
+        // outlive `Slots`. `UnsafeCell<MaybeUninit<T>>` has `T`'s layout.
+        let slots = Slots {
+            ptr: unsafe { NonNull::new_unchecked(map.base.add(slots_offset).cast()) },
+            len: cap,
+            owned: false,
+        };
+        let mut rb = Self::from_slots(slots, capacity);
+        *rb.head.get_mut() = head;
+        *rb.tail.get_mut() = tail;
+        rb.file = Some(map);
+        Ok(rb)
+    }
+
+    /// Forces a file-backed buffer's pages to disk (`msync`). Does nothing
+    /// for a heap-backed buffer.
+    ///
+    /// Not needed to survive a process crash (the kernel owns the pages),
+    /// only to survive losing the machine.
+    pub fn flush_mmap(&self) -> io::Result<()> {
+        match &self.file {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("llt-rs-{}-{}.ring", name, std::process::id()))
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    #[repr(C)]
+    struct Event {
+        id: u64,
+        price: f64,
+    }
+
+    #[test]
+    fn test_recovers_items_after_reopen() {
+        let path = temp_path("recover");
+        let _ = std::fs::remove_file(&path);
+
+        {
+            // SAFETY: `Event` is plain data; the file is ours.
+            let rb = unsafe { RingBuffer::<Event>::from_mmap(&path, 4) }.unwrap();
+            for id in 0..3 {
+                rb.send(Event { id, price: 1.5 }).unwrap();
+            }
+            assert_eq!(rb.recv(), Some(Event { id: 0, price: 1.5 }));
+        } // Unmapped here, like a process exit.
+
+        // SAFETY: As above.
+        let rb = unsafe { RingBuffer::<Event>::from_mmap(&path, 4) }.unwrap();
+        assert_eq!(rb.len(), 2);
+        assert_eq!(rb.recv(), Some(Event { id: 1, price: 1.5 }));
+        assert_eq!(rb.recv(), Some(Event { id: 2, price: 1.5 }));
+        assert_eq!(rb.recv(), None);
+
+        drop(rb);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_rejects_mismatched_layout() {
+        let path = temp_path("mismatch");
+        let _ = std::fs::remove_file(&path);
+
+        // SAFETY (all three): integer payloads; the file is ours.
+        drop(unsafe { RingBuffer::<u64>::from_mmap(&path, 8) }.unwrap());
+        let err = unsafe { RingBuffer::<u64>::from_mmap(&path, 16) }
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        // Same size in bytes, different element type.
+        let err = unsafe { RingBuffer::<[u32; 2]>::from_mmap(&path, 8) }
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_rejects_corrupt_counters() {
+        let path = temp_path("corrupt");
+        let _ = std::fs::remove_file(&path);
+
+        // SAFETY (both): `u64` payload; the file is ours.
+        let rb = unsafe { RingBuffer::<u64>::from_mmap(&path, 4) }.unwrap();
+        // Claim more queued items than the buffer can hold.
+        rb.file.as_ref().unwrap().set_head(5);
+        drop(rb);
+        let err = unsafe { RingBuffer::<u64>::from_mmap(&path, 4) }
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "channel")]
+    fn test_backs_a_channel() {
+        use crate::channel::channel_from_buffer;
+
+        let path = temp_path("channel");
+        let _ = std::fs::remove_file(&path);
+
+        {
+            // SAFETY: `u64` is valid for any bit pattern; the file is ours.
+            let rb = unsafe { RingBuffer::<u64>::from_mmap(&path, 8) }.unwrap();
+            let (tx, rx) = channel_from_buffer(rb);
+            tx.send(1);
+            tx.send(2);
+            assert_eq!(rx.recv(), Some(1));
+        }
+
+        // SAFETY: As above.
+        let rb = unsafe { RingBuffer::<u64>::from_mmap(&path, 8) }.unwrap();
+        let (_tx, rx) = channel_from_buffer(rb);
+        assert_eq!(rx.try_recv(), Some(2));
+
+        drop(rx);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,23 +1,118 @@
 #![doc = include_str!("README.md")]
 
-use crossbeam_utils::CachePadded;
+mod batch;
+mod command_queue;
+mod growable;
+mod io;
+#[cfg(all(feature = "mmap", unix))]
+mod mmap;
+mod orderings;
+mod seq_checked;
+
+pub use batch::BatchWriter;
+pub use command_queue::{Command, CommandQueue};
+pub use growable::GrowableRingBuffer;
+pub use io::{RingReader, RingWriter, byte_pipe};
+pub use orderings::{DefaultOrderings, Orderings, SeqCstOrderings};
+pub use seq_checked::{SeqCheckedRingBuffer, SequenceError};
+
+use crate::error::Error;
+pub use crossbeam_utils::CachePadded;
 use std::cell::UnsafeCell;
+use std::fmt;
+use std::marker::PhantomData;
 use std::mem::MaybeUninit;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::ops::{Deref, DerefMut};
+use std::ptr::NonNull;
+#[cfg(feature = "stats")]
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// Returned by [`RingBuffer::try_new`] when the backing storage can't be allocated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AllocError {
+    /// The capacity (in items) that was requested.
+    pub requested: usize,
+}
+
+impl fmt::Display for AllocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "failed to allocate a ring buffer with capacity {}",
+            self.requested
+        )
+    }
+}
+
+impl std::error::Error for AllocError {}
+
+/// Returns the capacity a ring buffer asked for `requested` slots would
+/// actually get: the next power of two (so 0 and 1 both give 1).
+///
+/// Being `const`, it works in static assertions, e.g. to check a memory
+/// budget at compile time.
+///
+/// # Panics
+/// Panics (a compile error in const contexts) if the result would overflow
+/// `usize`, the same case in which [`RingBuffer::try_new`] returns an error.
+pub const fn rounded_capacity(requested: usize) -> usize {
+    match requested.checked_next_power_of_two() {
+        Some(cap) => cap,
+        None => panic!("ring buffer capacity overflows usize"),
+    }
+}
+
+/// Why [`RingBuffer::try_recv`] returned no item.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecvState {
+    /// Nothing is queued right now, but more may arrive.
+    Empty,
+    /// The producer called [`RingBuffer::close`] and everything is drained.
+    Disconnected,
+}
+
+/// Number of buckets in [`RingBuffer::occupancy_histogram`].
+#[cfg(feature = "stats")]
+pub const OCCUPANCY_BUCKETS: usize = 16;
 
 /// A Single-Producer, Single-Consumer (SPSC) lock free ring buffer.
 /// This queue is "wait-free" (bounded time) for both producer and consumer.
 /// It does not block, but return `Err` or `None` if the queue is full or empty.
-pub struct RingBuffer<T> {
-    ///The buffer, allocated on the heap
+///
+/// # Thread Safety
+/// `RingBuffer<T>` is `Send + Sync` exactly when `T: Send`. A buffer of
+/// non-`Send` items (like `Rc`) cannot cross threads:
+///
+/// ```compile_fail
+/// use llt_rs::RingBuffer;
+/// use std::rc::Rc;
+///
+/// fn assert_send<T: Send>() {}
+/// assert_send::<RingBuffer<Rc<u8>>>();
+/// ```
+///
+/// # Memory Orderings
+/// `O` selects the atomic orderings used by `send`/`recv` (see [`Orderings`]).
+/// Leave it at the default unless you are experimenting with memory models.
+///
+/// # Drop Order
+/// Items still queued when the buffer is dropped are dropped in FIFO order
+/// (oldest first), the same order `recv` would have returned them in. This
+/// is a guarantee, not an implementation detail.
+pub struct RingBuffer<T, O = DefaultOrderings> {
+    ///The buffer, allocated on the heap (or, with `mmap`, in `file`)
     /// We use `UnsafeCell` for interior mutability (to write from `&self`).
     /// We use `MaybeUninit` to store uninitialized data and take ownership
     /// of `T's` when we `recv`
-    buffer: Box<[UnsafeCell<MaybeUninit<T>>]>,
+    buffer: Slots<T>,
 
     /// The capacity of the buffer, Must be a power of 2 (an optimization that allows us to use bit trick instead of modulo)
     cap: usize,
 
+    /// The capacity passed to the constructor, before rounding.
+    requested_cap: usize,
+
     /// The `head` counter.
     /// This is where the producer will write the *next* item.
     /// Only the producer modifies this.
@@ -29,44 +124,235 @@ pub struct RingBuffer<T> {
     /// Only the consumer modifies this
     /// Padded to prevent false sharing with `head`.
     tail: CachePadded<AtomicUsize>,
+
+    /// Set by the producer's `close`. Only read once the buffer looks empty.
+    closed: AtomicBool,
+
+    /// How full the buffer was after each successful `send`/`recv`,
+    /// bucketed over `0..=cap`.
+    #[cfg(feature = "stats")]
+    occupancy: [AtomicU64; OCCUPANCY_BUCKETS],
+
+    /// The file mapping holding `buffer` and a durable copy of `head` and
+    /// `tail`, for buffers made by `from_mmap`.
+    #[cfg(all(feature = "mmap", unix))]
+    file: Option<mmap::MmapFile>,
+
+    /// The ordering policy. Zero-sized.
+    _orderings: PhantomData<O>,
+}
+
+/// The slot array: a heap allocation we own, or (`mmap` feature) a region
+/// of the file mapping, which the `RingBuffer` keeps alive alongside it.
+struct Slots<T> {
+    ptr: NonNull<UnsafeCell<MaybeUninit<T>>>,
+    len: usize,
+    owned: bool,
+}
+
+impl<T> Slots<T> {
+    fn from_box(slots: Box<[UnsafeCell<MaybeUninit<T>>]>) -> Self {
+        let len = slots.len();
+        // SAFETY: `Box::into_raw` never returns null.
+        let ptr = unsafe { NonNull::new_unchecked(Box::into_raw(slots).cast()) };
+        Self {
+            ptr,
+            len,
+            owned: true,
+        }
+    }
+}
+
+impl<T> Deref for Slots<T> {
+    type Target = [UnsafeCell<MaybeUninit<T>>];
+
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: `ptr` points at `len` slots that live as long as `self`.
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<T> DerefMut for Slots<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // SAFETY: As in `deref`, and `&mut self` makes the access unique.
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<T> Drop for Slots<T> {
+    fn drop(&mut self) {
+        if self.owned {
+            // SAFETY: Owned slots came from `Box::into_raw` in `from_box`.
+            // The items themselves were already dropped by `RingBuffer`.
+            drop(unsafe {
+                Box::from_raw(std::ptr::slice_from_raw_parts_mut(
+                    self.ptr.as_ptr(),
+                    self.len,
+                ))
+            });
+        }
+    }
 }
 
 /// We can safely send the RingBuffer to other threads if T is Send
 /// `Unsafe` is not `Sync` BUT WE *know* we are only accessing
 /// the buffer safely from the *single* producer and *single* consumer.
 /// The `head` and `tail` atomics prevent reading/writing the same slot.
-unsafe impl<T: Send> Sync for RingBuffer<T> {}
-unsafe impl<T: Send> Send for RingBuffer<T> {}
+unsafe impl<T: Send, O> Sync for RingBuffer<T, O> {}
+unsafe impl<T: Send, O> Send for RingBuffer<T, O> {}
 
+// `new`/`try_new` live on the default-ordering type so that
+// `RingBuffer::new(n)` infers without annotations.
 impl<T> RingBuffer<T> {
     /// Creates a new SPSC ring buffer with *at least* the given capacity
     /// The actual capacity will be rounded up to the next power of 2.
+    ///
+    /// # Panics
+    /// Panics if the buffer cannot be allocated. Use [`RingBuffer::try_new`]
+    /// to handle that case gracefully.
     pub fn new(capacity: usize) -> Self {
-        // Round up to the next power of 2
+        match Self::try_new(capacity) {
+            Ok(rb) => rb,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Creates a new SPSC ring buffer, returning an error instead of aborting
+    /// if the capacity overflows or the allocation fails.
+    pub fn try_new(capacity: usize) -> Result<Self, AllocError> {
+        RingBuffer::try_with_orderings(capacity)
+    }
+
+    /// Creates a ring buffer with *exactly* `capacity` slots.
+    ///
+    /// Unlike [`RingBuffer::new`] this does not round up: it fails with
+    /// [`Error::CapacityNotPowerOfTwo`] instead, or [`Error::OutOfMemory`]
+    /// if the allocation fails.
+    pub fn try_new_exact(capacity: usize) -> Result<Self, Error> {
+        if !capacity.is_power_of_two() {
+            return Err(Error::CapacityNotPowerOfTwo);
+        }
+        Ok(RingBuffer::try_new(capacity)?)
+    }
+}
+
+impl<T, O: Orderings> RingBuffer<T, O> {
+    /// Creates a ring buffer that uses the orderings policy `O`.
+    ///
+    /// ```
+    /// use llt_rs::ring_buffer::{RingBuffer, SeqCstOrderings};
+    ///
+    /// let rb = RingBuffer::<u32, SeqCstOrderings>::with_orderings(8);
+    /// rb.send(1).unwrap();
+    /// assert_eq!(rb.recv(), Some(1));
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if the buffer cannot be allocated.
+    pub fn with_orderings(capacity: usize) -> Self {
+        match Self::try_with_orderings(capacity) {
+            Ok(rb) => rb,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Fallible version of [`RingBuffer::with_orderings`].
+    pub fn try_with_orderings(capacity: usize) -> Result<Self, AllocError> {
+        let err = AllocError {
+            requested: capacity,
+        };
+        // Round up to the next power of 2 (see `rounded_capacity`)
         //this allows us to replace a slow modulo with fast BITWISE-AND
-        let cap = capacity.next_power_of_two();
-        //Create a Vec and fill it with uninitialized data
-        let mut buffer = Vec::with_capacity(cap);
+        let cap = capacity.checked_next_power_of_two().ok_or(err)?;
+        //Create a Vec and fill it with uninitialized data.
+        // `try_reserve_exact` reports OOM as an error rather than aborting.
+        let mut buffer = Vec::new();
+        buffer.try_reserve_exact(cap).map_err(|_| err)?;
         for _ in 0..cap {
             buffer.push(UnsafeCell::new(MaybeUninit::uninit()));
         }
 
         // Convert the Vec to a Box<[]>
-        let buffer = buffer.into_boxed_slice();
+        let buffer = Slots::from_box(buffer.into_boxed_slice());
+
+        Ok(Self::from_slots(buffer, capacity))
+    }
 
+    /// Wraps a slot array of power-of-2 length as an empty buffer.
+    fn from_slots(buffer: Slots<T>, requested_cap: usize) -> Self {
         Self {
+            cap: buffer.len(),
             buffer,
-            cap,
+            requested_cap,
             head: CachePadded::new(AtomicUsize::new(0)),
             tail: CachePadded::new(AtomicUsize::new(0)),
+            closed: AtomicBool::new(false),
+            #[cfg(feature = "stats")]
+            occupancy: std::array::from_fn(|_| AtomicU64::new(0)),
+            #[cfg(all(feature = "mmap", unix))]
+            file: None,
+            _orderings: PhantomData,
         }
     }
 
+    /// Stores a new `head` (producer only). A file-backed buffer updates
+    /// the file's copy first, after the slots it covers were written.
+    #[inline(always)]
+    fn publish_head(&self, head: usize) {
+        #[cfg(all(feature = "mmap", unix))]
+        if let Some(file) = &self.file {
+            file.set_head(head);
+        }
+        self.head.store(head, O::PUBLISH);
+    }
+
+    /// Stores a new `tail` (consumer only). The file's copy is updated
+    /// first, so it never trails a slot the producer may already reuse.
+    #[inline(always)]
+    fn publish_tail(&self, tail: usize) {
+        #[cfg(all(feature = "mmap", unix))]
+        if let Some(file) = &self.file {
+            file.set_tail(tail);
+        }
+        self.tail.store(tail, O::PUBLISH);
+    }
+
     /// Returns the capacity of the ring buffer.
     pub fn capacity(&self) -> usize {
         self.cap
     }
 
+    /// Returns the capacity originally asked for, before it was rounded up
+    /// to [`capacity`](Self::capacity). Compare the two to detect rounding.
+    pub fn requested_capacity(&self) -> usize {
+        self.requested_cap
+    }
+
+    /// Moves the whole buffer (with every queued item) out, leaving an empty
+    /// buffer of the same capacity in its place.
+    ///
+    /// Requires `&mut self`, so no producer or consumer can be mid-operation.
+    /// Handy for double buffering: snapshot the queue, then process the
+    /// returned buffer offline.
+    ///
+    /// # Panics
+    /// Panics if the replacement buffer cannot be allocated.
+    pub fn take(&mut self) -> Self {
+        let mut fresh = Self::with_orderings(self.cap);
+        fresh.requested_cap = self.requested_cap;
+        std::mem::replace(self, fresh)
+    }
+
+    /// Returns how many items can be queued at the same time.
+    ///
+    /// The buffer is full when `head - tail == capacity`, so every slot is
+    /// usable and this equals [`capacity`](Self::capacity). Use this (rather
+    /// than `capacity`) when sizing batches, so your math stays correct if a
+    /// variant ever reserves an empty slot.
+    pub fn usable_capacity(&self) -> usize {
+        self.cap
+    }
+
     ///Returns the number of items currently in the buffer.
     /// This is a snapshot and maybe out of date immediately.
     pub fn len(&self) -> usize {
@@ -74,11 +360,62 @@ impl<T> RingBuffer<T> {
         let tail = self.tail.load(Ordering::Relaxed);
         head.wrapping_sub(tail)
     }
+    /// Returns `(sent, received)`: the raw `head` and `tail` counters (totals
+    /// modulo `usize`). `tail` is loaded first, so `received <= sent` holds.
+    #[cfg(feature = "channel")]
+    pub(crate) fn counters(&self) -> (usize, usize) {
+        let tail = self.tail.load(Ordering::Acquire);
+        let head = self.head.load(Ordering::Acquire);
+        (head, tail)
+    }
+
     ///Returns true if the buffer is empty.
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
 
+    /// Returns the total number of items ever sent (the raw `head` counter).
+    ///
+    /// This is a lifetime total modulo `usize` wrap-around, useful for
+    /// throughput accounting. Like `len`, it is a `Relaxed` snapshot.
+    pub fn produced(&self) -> usize {
+        self.head.load(Ordering::Relaxed)
+    }
+
+    /// Returns the total number of items ever received (the raw `tail` counter).
+    ///
+    /// This is a lifetime total modulo `usize` wrap-around, useful for
+    /// throughput accounting. Like `len`, it is a `Relaxed` snapshot.
+    pub fn consumed(&self) -> usize {
+        self.tail.load(Ordering::Relaxed)
+    }
+
+    /// Returns the raw `head` counter: the total number of items ever sent
+    /// (modulo `usize`). Only the producer stores to it, with `Release`.
+    ///
+    /// An escape hatch for embedding the buffer in a larger protocol (e.g.
+    /// a watermark-based flush). Load it with `Acquire` to synchronize with
+    /// the producer's writes.
+    ///
+    /// # Safety
+    /// The reference is for loads only. Storing to (or otherwise modifying)
+    /// the counter breaks the buffer's invariants and lets `recv` read
+    /// uninitialized or already-moved slots.
+    #[cfg(feature = "internals")]
+    pub unsafe fn head_atomic(&self) -> &AtomicUsize {
+        &self.head
+    }
+
+    /// Returns the raw `tail` counter: the total number of items ever
+    /// received (modulo `usize`). Only the consumer stores to it, with `Release`.
+    ///
+    /// # Safety
+    /// As for [`head_atomic`](Self::head_atomic): loads only.
+    #[cfg(feature = "internals")]
+    pub unsafe fn tail_atomic(&self) -> &AtomicUsize {
+        &self.tail
+    }
+
     /// sends a item into a buffer
     ///
     /// Fails if the buffer is full, returning an `Err(item)`.
@@ -86,10 +423,10 @@ impl<T> RingBuffer<T> {
     pub fn send(&self, item: T) -> Result<(), T> {
         // Load the current head and tail.
         // `head` can be Relaxed because only *we* can change it.
-        // `tail` must be `Acquire` to "see" the consumer's `Release`
+        // `tail` must be `Acquire` (O::OBSERVE) to "see" the consumer's `Release`
         // store, which signals that a slot has been freed.
         let head = self.head.load(Ordering::Relaxed);
-        let tail = self.tail.load(Ordering::Acquire);
+        let tail = self.tail.load(O::OBSERVE);
         //Check if the buffer is full
         // `wrapping_sub` handles counter wrap-around.
         if head.wrapping_sub(tail) == self.cap {
@@ -113,16 +450,80 @@ impl<T> RingBuffer<T> {
         // We use `Release` to ensure that the data write (above)
         // is *not* reordered *after* this store. This makes the
         // data visible to the consumer's `Acquire` load.
-        self.head.store(head.wrapping_add(1), Ordering::Release);
+        self.publish_head(head.wrapping_add(1));
+
+        #[cfg(feature = "stats")]
+        self.record_occupancy(head.wrapping_add(1).wrapping_sub(tail));
         Ok(())
     }
+
+    /// Returns the most recently sent item if the consumer hasn't taken it
+    /// yet, so the producer can coalesce a newer update into it in place.
+    ///
+    /// This is a *Producer* method.
+    ///
+    /// # Safety
+    /// The `tail` check only says the item was unconsumed at the moment of
+    /// the call. The consumer must not `recv` while the returned reference
+    /// is alive (e.g. it is paused, or runs on this same thread), otherwise
+    /// it would read the slot while we write it: a data race.
+    #[allow(clippy::mut_from_ref)]
+    pub unsafe fn peek_last_unconsumed_mut(&self) -> Option<&mut T> {
+        let head = self.head.load(Ordering::Relaxed);
+        let tail = self.tail.load(O::OBSERVE);
+        if head == tail {
+            return None;
+        }
+        let slot_idx = head.wrapping_sub(1) & (self.cap - 1);
+
+        // SAFETY: `tail < head`, so the slot holds an item we wrote and the
+        // consumer has not taken. The caller guarantees it won't take it
+        // while the reference lives. Our own `send` never touches this slot.
+        unsafe { Some((*self.buffer[slot_idx].get()).assume_init_mut()) }
+    }
+    /// Returns a reference to the oldest item without taking it.
+    ///
+    /// This is a *Consumer* method.
+    ///
+    /// # Safety
+    /// The item stays in the buffer, so only the consumer may call this,
+    /// and it must not `recv` while the reference is alive (the item would
+    /// be moved out from under it).
+    pub unsafe fn peek(&self) -> Option<&T> {
+        // SAFETY: Same contract as ours.
+        unsafe { self.peek_nth(0) }
+    }
+
+    /// Returns a reference to the item `n` positions behind the oldest one
+    /// (`peek_nth(0)` is [`peek`](Self::peek)), or `None` if fewer than
+    /// `n + 1` items are queued.
+    ///
+    /// This is a *Consumer* method, e.g. for a scheduler checking the
+    /// deadlines of the next few items.
+    ///
+    /// # Safety
+    /// As for [`peek`](Self::peek): only the consumer may call this, and it
+    /// must not `recv` while the reference is alive.
+    pub unsafe fn peek_nth(&self, n: usize) -> Option<&T> {
+        let tail = self.tail.load(Ordering::Relaxed);
+        let head = self.head.load(O::OBSERVE);
+        if n >= head.wrapping_sub(tail) {
+            return None;
+        }
+        let slot_idx = tail.wrapping_add(n) & (self.cap - 1);
+        // SAFETY: `tail + n < head`, so as in `recv` the slot holds a
+        // published item. The caller guarantees it isn't taken while the
+        // reference lives.
+        unsafe { Some((*self.buffer[slot_idx].get()).assume_init_ref()) }
+    }
+
     pub fn recv(&self) -> Option<T> {
         //Load the current head and tail.
         // `tail` can be Relaxed because only *we* change it.
-        // `head` must be `Acquire` to "see" the producer's `Release`
+        // `head` must be `Acquire` (O::OBSERVE) to "see" the producer's `Release`
         // store, which signals that data is available.
         let tail = self.tail.load(Ordering::Relaxed);
-        let head = self.head.load(Ordering::Acquire);
+        let head = self.head.load(O::OBSERVE);
 
         //Check if the buffer is empty
         if tail == head {
@@ -130,6 +531,13 @@ impl<T> RingBuffer<T> {
         }
 
         let slot_idx = tail & (self.cap - 1);
+        // Start pulling in the next item while the caller processes this one.
+        // Only if it is already published: touching a slot the producer is
+        // about to write would steal its cache line.
+        #[cfg(feature = "prefetch")]
+        if tail.wrapping_add(1) != head {
+            prefetch_read(self.buffer[tail.wrapping_add(1) & (self.cap - 1)].get());
+        }
         //Calculate the slot index.
         //SAFETY.
         //1. `&self` is ok because `UnsafeCell`.
@@ -148,14 +556,205 @@ impl<T> RingBuffer<T> {
         // We use `Release` to ensure that our "take" (the read)
         // is visible to the producer's `Acquire` load of `tail`.
 
-        self.tail.store(tail.wrapping_add(1), Ordering::Release);
+        self.publish_tail(tail.wrapping_add(1));
 
+        #[cfg(feature = "stats")]
+        self.record_occupancy(head.wrapping_sub(tail).wrapping_sub(1));
         Some(item)
     }
+
+    /// Returns a histogram of how full the buffer has been.
+    ///
+    /// Each successful `send` or `recv` adds one sample: the number of queued
+    /// items right after the operation, mapped onto [`OCCUPANCY_BUCKETS`]
+    /// equal-width buckets over `0..=capacity` (bucket 0 is "nearly empty",
+    /// the last is "nearly full"). A buffer that lives in the top buckets
+    /// probably needs enlarging.
+    ///
+    /// Samples are `Relaxed` and taken from each side's own view of the
+    /// counters, so treat this as a statistical picture, not an exact trace.
+    #[cfg(feature = "stats")]
+    pub fn occupancy_histogram(&self) -> [u64; OCCUPANCY_BUCKETS] {
+        std::array::from_fn(|i| self.occupancy[i].load(Ordering::Relaxed))
+    }
+
+    #[cfg(feature = "stats")]
+    fn record_occupancy(&self, occupied: usize) {
+        // u128 keeps `occupied * BUCKETS` from overflowing on huge buffers.
+        let bucket = occupied as u128 * OCCUPANCY_BUCKETS as u128 / (self.cap as u128 + 1);
+        self.occupancy[bucket as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Loads items from `items` until the buffer is full, during a
+    /// single-threaded setup phase. Returns how many were loaded.
+    ///
+    /// `&mut self` rules out a concurrent consumer, so this writes the slots
+    /// and bumps `head` directly, without the atomic publish of `send`. At
+    /// most as many items as there is room for are pulled from the iterator:
+    /// pass `&mut iter` to keep the rest.
+    pub fn fill_from(&mut self, items: impl IntoIterator<Item = T>) -> usize {
+        let tail = *self.tail.get_mut();
+        let free = self.cap - self.head.get_mut().wrapping_sub(tail);
+        let mut loaded = 0;
+        for item in items.into_iter().take(free) {
+            let head = self.head.get_mut();
+            self.buffer[*head & (self.cap - 1)].get_mut().write(item);
+            // Bumped per item, so a panicking iterator can't leak the earlier ones.
+            *head = head.wrapping_add(1);
+            loaded += 1;
+        }
+        #[cfg(all(feature = "mmap", unix))]
+        if let Some(file) = &self.file {
+            file.set_head(*self.head.get_mut());
+        }
+        loaded
+    }
+
+    /// Like [`send`](Self::send), but returns [`Error::Full`] instead of the item.
+    ///
+    /// The rejected item is dropped.
+    pub fn send_err(&self, item: T) -> Result<(), Error> {
+        self.send(item).map_err(|_| Error::Full)
+    }
+
+    /// Marks the stream as finished. Items already sent can still be received.
+    ///
+    /// This is a *Producer* method. It only informs [`try_recv`](Self::try_recv);
+    /// `send` keeps working.
+    pub fn close(&self) {
+        // Release: a consumer that sees the flag also sees every earlier send.
+        self.closed.store(true, Ordering::Release);
+    }
+
+    /// Returns true once the producer has called [`close`](Self::close).
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Acquire)
+    }
+
+    /// Like [`recv`](Self::recv), but tells "empty for now" apart from
+    /// "closed and drained".
+    ///
+    /// This is the *Consumer* method. After `close`, the remaining items are
+    /// still returned as `Ok`, then every call reports `Disconnected`.
+    pub fn try_recv(&self) -> Result<T, RecvState> {
+        if let Some(item) = self.recv() {
+            return Ok(item);
+        }
+        if self.is_closed() {
+            // The producer may have sent a last item between our `recv` and
+            // `close`; the flag's Acquire makes it visible now.
+            return self.recv().ok_or(RecvState::Disconnected);
+        }
+        Err(RecvState::Empty)
+    }
+
+    /// Like [`recv`](Self::recv), but returns [`Error::Empty`] instead of `None`.
+    pub fn recv_err(&self) -> Result<T, Error> {
+        self.recv().ok_or(Error::Empty)
+    }
+
+    /// Receives an item along with the number of items still queued after it.
+    ///
+    /// This is the *Consumer* method. The backlog is computed from a single
+    /// `Acquire` load of `head` after the read, which saves a separate `len()`
+    /// call on hot paths that adapt to queue depth.
+    pub fn recv_with_backlog(&self) -> Option<(T, usize)> {
+        let item = self.recv()?;
+        Some((item, self.backlog()))
+    }
+
+    /// The number of queued items as seen by the *Consumer*: its own `tail`
+    /// and a single `Acquire` load of `head`.
+    pub(crate) fn backlog(&self) -> usize {
+        // Only we modify `tail`, so a Relaxed load sees our own store.
+        let tail = self.tail.load(Ordering::Relaxed);
+        let head = self.head.load(O::OBSERVE);
+        head.wrapping_sub(tail)
+    }
+}
+
+/// Ring buffers whose slots each occupy their own cache line(s).
+///
+/// With small items, neighbouring slots share a cache line, so the producer
+/// writing slot `n + 1` can invalidate the line the consumer is reading
+/// slot `n` from. Padding every slot trades memory for avoiding that.
+impl<T> RingBuffer<CachePadded<T>> {
+    /// Creates a buffer of cache-padded slots with *at least* the given
+    /// capacity (rounded up to the next power of 2).
+    ///
+    /// # Panics
+    /// Panics if the buffer cannot be allocated.
+    pub fn new_padded(capacity: usize) -> Self {
+        Self::new(capacity)
+    }
+
+    /// Like [`send`](Self::send), wrapping the item in its padding.
+    ///
+    /// This is the *Producer* method.
+    pub fn send_padded(&self, item: T) -> Result<(), T> {
+        self.send(CachePadded::new(item))
+            .map_err(CachePadded::into_inner)
+    }
+
+    /// Like [`recv`](Self::recv), unwrapping the item from its padding.
+    ///
+    /// This is the *Consumer* method.
+    pub fn recv_padded(&self) -> Option<T> {
+        self.recv().map(CachePadded::into_inner)
+    }
+}
+
+impl<T: Copy, O: Orderings> RingBuffer<T, O> {
+    /// Sends a copy of `*item`, copying straight from the borrow into the slot.
+    ///
+    /// This is the *Producer* method. Unlike `send(*item)`, there is no
+    /// intermediate stack copy, which matters for large `[u8; N]`-style
+    /// payloads. Fails with [`Error::Full`] if the buffer is full.
+    pub fn send_copy(&self, item: &T) -> Result<(), Error> {
+        let head = self.head.load(Ordering::Relaxed);
+        let tail = self.tail.load(O::OBSERVE);
+        if head.wrapping_sub(tail) == self.cap {
+            return Err(Error::Full);
+        }
+        let slot_idx = head & (self.cap - 1);
+
+        // SAFETY: Same as `send`: the slot is ours until we publish `head`.
+        // `item` is a valid `T` and can't overlap the slot (which no
+        // reference points into while it is unpublished). `T: Copy`, so
+        // duplicating its bytes is a valid copy.
+        unsafe {
+            std::ptr::copy_nonoverlapping(item, (*self.buffer[slot_idx].get()).as_mut_ptr(), 1);
+        }
+        self.publish_head(head.wrapping_add(1));
+
+        #[cfg(feature = "stats")]
+        self.record_occupancy(head.wrapping_add(1).wrapping_sub(tail));
+        Ok(())
+    }
+
+    /// Copies the queued items, oldest first, without consuming them.
+    ///
+    /// Takes `&mut self` so no producer or consumer can be running: handy
+    /// for dumping the queue state from a breakpoint or a diagnostic hook.
+    pub fn snapshot(&mut self) -> Vec<T> {
+        let head = *self.head.get_mut();
+        let tail = *self.tail.get_mut();
+        let mut items = Vec::with_capacity(head.wrapping_sub(tail));
+        let mut i = tail;
+        while i != head {
+            // SAFETY: Slots from `tail` to `head` hold initialized items, and
+            // `&mut self` rules out a concurrent `recv`. `T: Copy`, so the
+            // copy leaves the slot's item intact.
+            items.push(unsafe { (*self.buffer[i & (self.cap - 1)].get()).assume_init_read() });
+            i = i.wrapping_add(1);
+        }
+        items
+    }
 }
 
 /// We must implement Drop to clean up any `T` a left in the buffer.
-impl<T> Drop for RingBuffer<T> {
+/// Items are dropped oldest first (tail to head), see "Drop Order" above.
+impl<T, O> Drop for RingBuffer<T, O> {
     fn drop(&mut self) {
         //We are in `&MUT self`, so no other threads can be accessing
         // the buffer, We can use `Relaxed` ordering;
@@ -178,12 +777,154 @@ impl<T> Drop for RingBuffer<T> {
         }
     }
 }
+/// Hints the CPU to load `ptr`'s cache line. A no-op on targets other than
+/// x86_64 and aarch64.
+#[cfg(feature = "prefetch")]
+#[inline(always)]
+fn prefetch_read<T>(ptr: *const T) {
+    #[cfg(target_arch = "x86_64")]
+    // SAFETY: A prefetch is only a hint; it never faults, even on bad addresses.
+    unsafe {
+        use std::arch::x86_64::{_MM_HINT_T0, _mm_prefetch};
+        _mm_prefetch::<_MM_HINT_T0>(ptr.cast());
+    }
+    #[cfg(target_arch = "aarch64")]
+    // SAFETY: As above; `prfm` doesn't fault or touch registers besides its input.
+    unsafe {
+        std::arch::asm!("prfm pldl1keep, [{0}]", in(reg) ptr, options(nostack, readonly, preserves_flags));
+    }
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    let _ = ptr;
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::sync::Arc;
     use std::thread;
 
+    #[test]
+    fn test_head_and_tail_on_separate_cache_lines() {
+        // Guards the padding: a refactor that co-locates the two counters
+        // would silently reintroduce false sharing between the threads.
+        const CACHE_LINE: usize = 64;
+        let rb = RingBuffer::<u64>::new(4);
+        let head = std::ptr::addr_of!(rb.head) as usize;
+        let tail = std::ptr::addr_of!(rb.tail) as usize;
+        assert!(
+            head.abs_diff(tail) >= CACHE_LINE,
+            "head and tail are only {} bytes apart",
+            head.abs_diff(tail)
+        );
+    }
+
+    #[test]
+    fn test_try_recv_drains_before_disconnected() {
+        let rb = RingBuffer::new(8);
+        assert_eq!(rb.try_recv(), Err(RecvState::Empty));
+
+        rb.send(1).unwrap();
+        rb.send(2).unwrap();
+        assert_eq!(rb.try_recv(), Ok(1));
+        rb.send(3).unwrap();
+        rb.close();
+
+        assert_eq!(rb.try_recv(), Ok(2));
+        assert_eq!(rb.try_recv(), Ok(3));
+        assert_eq!(rb.try_recv(), Err(RecvState::Disconnected));
+        assert_eq!(rb.try_recv(), Err(RecvState::Disconnected));
+    }
+
+    #[test]
+    fn test_send_copy_large_array() {
+        let rb = RingBuffer::<[u8; 4096]>::new(2);
+        let mut payload = [0u8; 4096];
+        for (i, b) in payload.iter_mut().enumerate() {
+            *b = i as u8;
+        }
+
+        rb.send_copy(&payload).unwrap();
+        rb.send_copy(&payload).unwrap();
+        assert_eq!(rb.send_copy(&payload), Err(Error::Full));
+
+        let first = rb.recv().unwrap();
+        assert_eq!(first[..], payload[..]);
+        assert_eq!(rb.len(), 1);
+    }
+
+    #[test]
+    fn test_padded_slots() {
+        let rb = RingBuffer::<CachePadded<u64>>::new_padded(4);
+        for i in 0..4 {
+            rb.send_padded(i).unwrap();
+        }
+        assert_eq!(rb.send_padded(99), Err(99));
+
+        // Every slot starts on its own cache line.
+        for slot in rb.buffer.iter() {
+            assert_eq!(slot.get() as usize % 64, 0);
+        }
+        assert!(std::mem::size_of::<CachePadded<u64>>() >= 64);
+
+        for i in 0..4 {
+            assert_eq!(rb.recv_padded(), Some(i));
+        }
+        assert_eq!(rb.recv_padded(), None);
+    }
+
+    #[test]
+    fn test_snapshot_leaves_items_queued() {
+        let mut rb = RingBuffer::new(4);
+        // Rotate first so the live items wrap around the end of the buffer.
+        for i in 0..3 {
+            rb.send(i).unwrap();
+            rb.recv().unwrap();
+        }
+        for i in 10..14 {
+            rb.send(i).unwrap();
+        }
+
+        assert_eq!(rb.snapshot(), vec![10, 11, 12, 13]);
+        assert_eq!(rb.len(), 4);
+        for i in 10..14 {
+            assert_eq!(rb.recv(), Some(i));
+        }
+        assert!(rb.snapshot().is_empty());
+    }
+
+    #[test]
+    fn test_peek_last_unconsumed_mut_coalesces() {
+        let rb = RingBuffer::new(4);
+        // SAFETY (for each call below): the consumer runs on this thread.
+        assert!(unsafe { rb.peek_last_unconsumed_mut() }.is_none());
+
+        rb.send(100).unwrap();
+        rb.send(101).unwrap();
+        *unsafe { rb.peek_last_unconsumed_mut() }.unwrap() = 105;
+        assert_eq!(rb.len(), 2);
+
+        assert_eq!(rb.recv(), Some(100));
+        assert_eq!(rb.recv(), Some(105));
+        // Once consumed, there is nothing left to coalesce into.
+        assert!(unsafe { rb.peek_last_unconsumed_mut() }.is_none());
+    }
+
+    fn assert_send<T: Send>() {}
+    fn assert_sync<T: Sync>() {}
+
+    #[test]
+    fn test_auto_traits() {
+        // These are compile-time checks; the test "passes" by compiling.
+        assert_send::<RingBuffer<u64>>();
+        assert_sync::<RingBuffer<u64>>();
+        assert_send::<RingBuffer<String>>();
+        assert_sync::<RingBuffer<String>>();
+        // `Cell` is Send but not Sync. Items only ever *move* between
+        // threads, so the buffer is still Sync.
+        assert_send::<RingBuffer<std::cell::Cell<u8>>>();
+        assert_sync::<RingBuffer<std::cell::Cell<u8>>>();
+    }
+
     #[test]
     fn test_single_thread_send_recv() {
         let rb = RingBuffer::new(4);
@@ -226,6 +967,285 @@ mod tests {
         assert_eq!(rb.len(), 0);
     }
 
+    #[test]
+    fn test_take_swaps_in_empty_buffer() {
+        let mut rb = RingBuffer::new(4);
+        for i in 0..4 {
+            rb.send(i).unwrap();
+        }
+
+        let taken = rb.take();
+        assert!(rb.is_empty());
+        assert_eq!(rb.capacity(), 4);
+        rb.send(99).unwrap();
+
+        assert_eq!(taken.len(), 4);
+        for i in 0..4 {
+            assert_eq!(taken.recv(), Some(i));
+        }
+        assert_eq!(taken.recv(), None);
+    }
+
+    #[test]
+    fn test_requested_capacity() {
+        let rb = RingBuffer::<u8>::new(1000);
+        assert_eq!(rb.requested_capacity(), 1000);
+        assert_eq!(rb.capacity(), 1024);
+
+        let exact = RingBuffer::<u8>::new(64);
+        assert_eq!(exact.requested_capacity(), exact.capacity());
+    }
+
+    #[test]
+    fn test_usable_capacity() {
+        let rb = RingBuffer::new(8);
+        assert_eq!(rb.usable_capacity(), rb.capacity());
+
+        for i in 0..rb.usable_capacity() {
+            rb.send(i).unwrap();
+        }
+        assert_eq!(rb.send(99), Err(99));
+    }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn test_occupancy_histogram() {
+        let rb = RingBuffer::new(16);
+        assert_eq!(rb.occupancy_histogram(), [0; OCCUPANCY_BUCKETS]);
+
+        // Filling 1..=16 walks through every bucket exactly once.
+        for i in 0..16 {
+            rb.send(i).unwrap();
+        }
+        assert_eq!(rb.occupancy_histogram(), [1; OCCUPANCY_BUCKETS]);
+
+        // A failed send doesn't count.
+        assert!(rb.send(99).is_err());
+
+        // Draining samples 15..=0: bucket 0 gets both 1 and 0.
+        while rb.recv().is_some() {}
+        let hist = rb.occupancy_histogram();
+        assert_eq!(hist.iter().sum::<u64>(), 32);
+        assert_eq!(hist[0], 3);
+        assert_eq!(hist[OCCUPANCY_BUCKETS - 1], 1);
+    }
+
+    #[test]
+    fn test_recv_with_backlog() {
+        let rb = RingBuffer::new(8);
+        for i in 0..5 {
+            rb.send(i).unwrap();
+        }
+
+        for (i, expected_backlog) in [4, 3, 2, 1, 0].into_iter().enumerate() {
+            assert_eq!(rb.recv_with_backlog(), Some((i, expected_backlog)));
+        }
+        assert_eq!(rb.recv_with_backlog(), None);
+    }
+
+    #[test]
+    fn test_produced_consumed_counters() {
+        let rb = RingBuffer::new(4);
+        assert_eq!((rb.produced(), rb.consumed()), (0, 0));
+
+        // Cycle through the buffer a few times so the counters pass `cap`.
+        for i in 0..10 {
+            rb.send(i).unwrap();
+            rb.send(i).unwrap();
+            rb.recv().unwrap();
+            rb.recv().unwrap();
+        }
+        rb.send(1).unwrap();
+        rb.send(2).unwrap();
+        rb.send(3).unwrap();
+        rb.recv().unwrap();
+
+        assert_eq!(rb.produced(), 23);
+        assert_eq!(rb.consumed(), 21);
+        assert_eq!(rb.len(), rb.produced() - rb.consumed());
+    }
+
+    /// A buffer whose counters both start at `start`, as if `start` items
+    /// had already passed through it.
+    fn with_counters<T>(capacity: usize, start: usize) -> RingBuffer<T> {
+        let rb = RingBuffer::new(capacity);
+        rb.head.store(start, Ordering::Relaxed);
+        rb.tail.store(start, Ordering::Relaxed);
+        rb
+    }
+
+    #[test]
+    fn test_counters_wrap_past_usize_max() {
+        let rb = with_counters(4, usize::MAX - 2);
+        assert!(rb.is_empty());
+
+        // Fill across the wrap: head ends at 1, tail is still at MAX - 2.
+        for i in 0..4 {
+            rb.send(i).unwrap();
+            assert_eq!(rb.len(), i + 1);
+        }
+        assert_eq!(rb.produced(), 1);
+        assert_eq!(rb.send(99), Err(99), "must be full, not empty");
+
+        for i in 0..4 {
+            assert_eq!(rb.recv_with_backlog(), Some((i, 3 - i)));
+        }
+        assert_eq!(rb.recv(), None, "must be empty, not full");
+
+        // Keep cycling well past the wrap point.
+        for i in 0..20 {
+            rb.send(i).unwrap();
+            assert_eq!(rb.len(), 1);
+            assert_eq!(rb.recv(), Some(i));
+        }
+        assert_eq!(rb.consumed(), 21);
+    }
+
+    #[test]
+    fn test_drop_across_wrap() {
+        let item = Arc::new(());
+        let rb = with_counters(4, usize::MAX - 1);
+        for _ in 0..3 {
+            rb.send(item.clone()).unwrap();
+        }
+        // Straddles the wrap: slots at MAX - 1, MAX and 0.
+        drop(rb);
+        assert_eq!(Arc::strong_count(&item), 1);
+    }
+
+    #[cfg(feature = "internals")]
+    #[test]
+    fn test_raw_atomics_track_counts() {
+        let rb = RingBuffer::new(4);
+        for i in 0..3 {
+            rb.send(i).unwrap();
+        }
+        rb.recv();
+
+        // SAFETY: Loads only.
+        let (head, tail) = unsafe { (rb.head_atomic(), rb.tail_atomic()) };
+        assert_eq!(head.load(Ordering::Acquire), 3);
+        assert_eq!(tail.load(Ordering::Acquire), 1);
+
+        rb.send(3).unwrap();
+        assert_eq!(head.load(Ordering::Acquire), 4);
+        assert_eq!(
+            head.load(Ordering::Acquire) - tail.load(Ordering::Acquire),
+            rb.len()
+        );
+    }
+
+    #[test]
+    fn test_peek_leaves_item_queued() {
+        let rb = RingBuffer::new(2);
+        // SAFETY: Single-threaded, and no `recv` while a reference is held.
+        unsafe {
+            assert_eq!(rb.peek(), None);
+            rb.send(7).unwrap();
+            assert_eq!(rb.peek(), Some(&7));
+        }
+        assert_eq!(rb.len(), 1);
+        assert_eq!(rb.recv(), Some(7));
+    }
+
+    #[test]
+    fn test_rounded_capacity() {
+        const _: () = assert!(rounded_capacity(1000) == 1024);
+        let cases = [(0, 1), (1, 1), (3, 4), (64, 64), (1000, 1024)];
+        for (requested, expected) in cases {
+            assert_eq!(rounded_capacity(requested), expected);
+            assert_eq!(RingBuffer::<u8>::new(requested).capacity(), expected);
+        }
+
+        let too_big = usize::MAX / 2 + 2;
+        assert!(std::panic::catch_unwind(|| rounded_capacity(too_big)).is_err());
+        assert!(RingBuffer::<u8>::try_new(too_big).is_err());
+    }
+
+    #[test]
+    fn test_fill_from_then_concurrent_recv() {
+        let mut rb = RingBuffer::new(8);
+        rb.send(0).unwrap();
+        let mut source = 1..100;
+        assert_eq!(rb.fill_from(&mut source), 7);
+        assert_eq!(source.next(), Some(8), "took more than it loaded");
+        assert_eq!(rb.fill_from([99]), 0);
+
+        let rb = Arc::new(rb);
+        let consumer = {
+            let rb = rb.clone();
+            thread::spawn(move || {
+                let mut got = Vec::new();
+                while got.len() < 12 {
+                    if let Some(v) = rb.recv() {
+                        got.push(v);
+                    }
+                }
+                got
+            })
+        };
+        for i in 8..12 {
+            while rb.send(i).is_err() {}
+        }
+        assert_eq!(consumer.join().unwrap(), (0..12).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_peek_nth_in_order() {
+        let rb = RingBuffer::new(8);
+        // Start mid-buffer so the window wraps.
+        for i in 0..6 {
+            rb.send(i).unwrap();
+            rb.recv().unwrap();
+        }
+        for i in 10..15 {
+            rb.send(i).unwrap();
+        }
+        // SAFETY: Single-threaded, and no `recv` while a reference is held.
+        unsafe {
+            for n in 0..5 {
+                assert_eq!(rb.peek_nth(n), Some(&(10 + n)));
+            }
+            assert_eq!(rb.peek_nth(5), None);
+            assert_eq!(rb.peek_nth(usize::MAX), None);
+        }
+        assert_eq!(rb.len(), 5);
+    }
+
+    #[test]
+    fn test_drop_order_is_fifo() {
+        struct Recorder(u32, Arc<std::sync::Mutex<Vec<u32>>>);
+        impl Drop for Recorder {
+            fn drop(&mut self) {
+                self.1.lock().unwrap().push(self.0);
+            }
+        }
+
+        let order = Arc::new(std::sync::Mutex::new(Vec::new()));
+        // Start near the wrap so the queued items straddle it.
+        let rb = with_counters(4, usize::MAX - 1);
+        for id in 0..4 {
+            rb.send(Recorder(id, order.clone())).ok().unwrap();
+        }
+        drop(rb.recv());
+        drop(rb);
+        assert_eq!(*order.lock().unwrap(), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_try_new_huge_capacity() {
+        // Rounds past usize::MAX.
+        let err = RingBuffer::<u64>::try_new(usize::MAX).err().unwrap();
+        assert_eq!(err.requested, usize::MAX);
+
+        // Rounds fine, but the byte size is impossible to allocate.
+        assert!(RingBuffer::<u64>::try_new(usize::MAX / 2).is_err());
+
+        // Sane requests still work.
+        let rb = RingBuffer::<u64>::try_new(1000).unwrap();
+        assert_eq!(rb.capacity(), 1024);
+    }
+
     #[test]
     fn test_multi_thread_spsc() {
         // Use Arc to share the RingBuffer between threads
@@ -266,6 +1286,39 @@ mod tests {
         consumer_thread.join().unwrap();
     }
 
+    /// The multi-threaded SPSC workload, generic over the ordering policy.
+    fn run_spsc_workload<O: Orderings>() {
+        let rb = Arc::new(RingBuffer::<usize, O>::with_orderings(1024));
+        let num_items = 200_000;
+
+        let producer_rb = rb.clone();
+        let producer = thread::spawn(move || {
+            for i in 0..num_items {
+                while producer_rb.send(i).is_err() {
+                    thread::yield_now();
+                }
+            }
+        });
+
+        let mut next_expected = 0;
+        while next_expected < num_items {
+            match rb.recv() {
+                Some(item) => {
+                    assert_eq!(item, next_expected);
+                    next_expected += 1;
+                }
+                None => thread::yield_now(),
+            }
+        }
+        producer.join().unwrap();
+    }
+
+    #[test]
+    fn test_orderings_variants() {
+        run_spsc_workload::<DefaultOrderings>();
+        run_spsc_workload::<SeqCstOrderings>();
+    }
+
     #[test]
     fn test_drop_cleanup() {
         // A simple type to track drops
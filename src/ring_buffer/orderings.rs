@@ -0,0 +1,48 @@
+//! Memory-ordering policies for [`RingBuffer`](super::RingBuffer).
+//!
+//! **This is an expert knob.** The default, [`DefaultOrderings`], is the
+//! minimal correct choice (Acquire/Release). The alternatives exist for
+//! experimenting with memory models and benchmarking; the trait is sealed so
+//! only orderings that keep the buffer sound can be plugged in.
+
+use std::sync::atomic::Ordering;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Chooses the atomic orderings used by `send` and `recv`.
+///
+/// Each side reads its *own* counter with `Relaxed` (only it writes that
+/// counter). These two constants control how each side talks to the other.
+pub trait Orderings: sealed::Sealed + Send + Sync + 'static {
+    /// Used to load the *other* side's counter (`tail` in `send`, `head` in `recv`).
+    /// Must be at least `Acquire`.
+    const OBSERVE: Ordering;
+    /// Used to store our own counter after touching a slot (`head` in `send`,
+    /// `tail` in `recv`). Must be at least `Release`.
+    const PUBLISH: Ordering;
+}
+
+/// The default: `Acquire` loads paired with `Release` stores.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultOrderings;
+
+impl sealed::Sealed for DefaultOrderings {}
+impl Orderings for DefaultOrderings {
+    const OBSERVE: Ordering = Ordering::Acquire;
+    const PUBLISH: Ordering = Ordering::Release;
+}
+
+/// Sequentially consistent loads and stores on both sides.
+///
+/// Strictly stronger (and usually slower) than the default. Useful as a
+/// baseline when reasoning about or benchmarking the weaker orderings.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SeqCstOrderings;
+
+impl sealed::Sealed for SeqCstOrderings {}
+impl Orderings for SeqCstOrderings {
+    const OBSERVE: Ordering = Ordering::SeqCst;
+    const PUBLISH: Ordering = Ordering::SeqCst;
+}
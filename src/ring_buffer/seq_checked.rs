@@ -0,0 +1,145 @@
+use super::RingBuffer;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Returned by [`SeqCheckedRingBuffer::recv`] when an item arrives out of sequence.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SequenceError {
+    /// The sequence number the consumer expected next.
+    pub expected: u64,
+    /// The sequence number it actually found.
+    pub found: u64,
+}
+
+impl fmt::Display for SequenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "sequence gap: expected {}, found {}",
+            self.expected, self.found
+        )
+    }
+}
+
+impl std::error::Error for SequenceError {}
+
+/// A debugging wrapper that tags every item with a sequence number.
+///
+/// `send` stamps each item with the next `u64`, and `recv` checks that it is
+/// exactly one more than the last one received. A gap or repeat means an item
+/// was lost, duplicated, or a slot was corrupted. It costs 8 bytes per slot
+/// and two extra counters, so use it to chase bugs, not in production.
+pub struct SeqCheckedRingBuffer<T> {
+    inner: RingBuffer<(u64, T)>,
+    /// Only the producer touches this.
+    next_send: AtomicU64,
+    /// Only the consumer touches this.
+    next_recv: AtomicU64,
+}
+
+impl<T> SeqCheckedRingBuffer<T> {
+    /// Creates a buffer with *at least* the given capacity
+    /// (rounded up to the next power of 2, like [`RingBuffer::new`]).
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: RingBuffer::new(capacity),
+            next_send: AtomicU64::new(0),
+            next_recv: AtomicU64::new(0),
+        }
+    }
+
+    /// Sends an item, stamping it with the next sequence number.
+    ///
+    /// This is the *Producer* method. Fails with `Err(item)` if full, in
+    /// which case the sequence number is not consumed.
+    pub fn send(&self, item: T) -> Result<(), T> {
+        // Relaxed is enough: only the producer reads or writes `next_send`.
+        let seq = self.next_send.load(Ordering::Relaxed);
+        self.inner.send((seq, item)).map_err(|(_, item)| item)?;
+        self.next_send.store(seq.wrapping_add(1), Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Receives an item and checks its sequence number.
+    ///
+    /// This is the *Consumer* method. Returns `Ok(None)` if empty. On a gap
+    /// the item is discarded and the check resynchronizes on it, so a single
+    /// fault is reported once rather than on every later `recv`.
+    pub fn recv(&self) -> Result<Option<T>, SequenceError> {
+        let Some((seq, item)) = self.inner.recv() else {
+            return Ok(None);
+        };
+        let expected = self.next_recv.load(Ordering::Relaxed);
+        self.next_recv.store(seq.wrapping_add(1), Ordering::Relaxed);
+        if seq != expected {
+            return Err(SequenceError {
+                expected,
+                found: seq,
+            });
+        }
+        Ok(Some(item))
+    }
+
+    /// Returns the capacity of the underlying buffer.
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_contiguous_sequences_pass() {
+        let rb = Arc::new(SeqCheckedRingBuffer::new(16));
+        let producer = rb.clone();
+
+        let handle = thread::spawn(move || {
+            for i in 0..10_000u32 {
+                let mut item = i;
+                while let Err(back) = producer.send(item) {
+                    item = back;
+                    thread::yield_now();
+                }
+            }
+        });
+
+        let mut received = 0;
+        while received < 10_000 {
+            match rb.recv().unwrap() {
+                Some(v) => {
+                    assert_eq!(v, received);
+                    received += 1;
+                }
+                None => thread::yield_now(),
+            }
+        }
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_detects_corrupted_counter() {
+        let rb = SeqCheckedRingBuffer::new(8);
+        rb.send('a').unwrap();
+
+        // Simulate a lost item: the producer skips sequence 1.
+        rb.next_send.fetch_add(1, Ordering::Relaxed);
+        rb.send('b').unwrap();
+        rb.send('c').unwrap();
+
+        assert_eq!(rb.recv(), Ok(Some('a')));
+        assert_eq!(
+            rb.recv(),
+            Err(SequenceError {
+                expected: 1,
+                found: 2
+            })
+        );
+        // Resynchronized: the fault is only reported once.
+        assert_eq!(rb.recv(), Ok(Some('c')));
+        assert_eq!(rb.recv(), Ok(None));
+    }
+}
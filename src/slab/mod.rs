@@ -0,0 +1,224 @@
+#![doc = include_str!("README.md")]
+
+use std::fmt;
+use std::marker::PhantomData;
+
+/// Marks the end of the free list.
+const NONE: usize = usize::MAX;
+
+/// A single slot: either holds a live value or links to the next free slot.
+enum Slot<T> {
+    Occupied(T),
+    Vacant { next_free: usize },
+}
+
+/// A fixed-capacity slab allocator.
+///
+/// All slots are allocated up front. `insert` and `remove` are O(1) and
+/// never touch the global allocator. Values are dropped correctly when
+/// they are removed (the value is returned to you) or when the slab drops.
+pub struct Slab<T> {
+    slots: Box<[Slot<T>]>,
+    /// Index of the first vacant slot, or `NONE` if the slab is full.
+    free_head: usize,
+    len: usize,
+}
+
+/// A small, copyable key referring to a value stored in a [`Slab`].
+///
+/// Keys are plain indices: once a value is removed, its slot (and therefore
+/// its key) may be reused by a later `insert`.
+pub struct SlabRef<T> {
+    index: usize,
+    // `fn() -> T` keeps the key `Send`/`Sync`/`Copy` regardless of `T`.
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> SlabRef<T> {
+    /// Returns the raw slot index behind this key.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl<T> Clone for SlabRef<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for SlabRef<T> {}
+
+impl<T> PartialEq for SlabRef<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+    }
+}
+
+impl<T> Eq for SlabRef<T> {}
+
+impl<T> fmt::Debug for SlabRef<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SlabRef").field(&self.index).finish()
+    }
+}
+
+impl<T> Slab<T> {
+    /// Creates a slab with room for exactly `capacity` values.
+    pub fn new(capacity: usize) -> Self {
+        // Every vacant slot points at the next one; the last ends the list.
+        let slots = (0..capacity)
+            .map(|i| Slot::Vacant {
+                next_free: if i + 1 < capacity { i + 1 } else { NONE },
+            })
+            .collect();
+
+        Self {
+            slots,
+            free_head: if capacity > 0 { 0 } else { NONE },
+            len: 0,
+        }
+    }
+
+    /// Stores `value` in a free slot and returns its key.
+    ///
+    /// If the slab is full, the value is handed back as `Err(value)`.
+    pub fn insert(&mut self, value: T) -> Result<SlabRef<T>, T> {
+        let index = self.free_head;
+        if index == NONE {
+            return Err(value);
+        }
+
+        let next_free = match self.slots[index] {
+            Slot::Vacant { next_free } => next_free,
+            Slot::Occupied(_) => unreachable!("free list points at an occupied slot"),
+        };
+        self.slots[index] = Slot::Occupied(value);
+        self.free_head = next_free;
+        self.len += 1;
+
+        Ok(SlabRef {
+            index,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Removes the value behind `key`, returning it, and frees the slot.
+    ///
+    /// Returns `None` if the slot is already vacant.
+    pub fn remove(&mut self, key: SlabRef<T>) -> Option<T> {
+        let slot = self.slots.get_mut(key.index)?;
+        if let Slot::Vacant { .. } = slot {
+            return None;
+        }
+
+        let old = std::mem::replace(
+            slot,
+            Slot::Vacant {
+                next_free: self.free_head,
+            },
+        );
+        self.free_head = key.index;
+        self.len -= 1;
+
+        match old {
+            Slot::Occupied(value) => Some(value),
+            Slot::Vacant { .. } => unreachable!(),
+        }
+    }
+
+    /// Returns a reference to the value behind `key`, if it is occupied.
+    pub fn get(&self, key: SlabRef<T>) -> Option<&T> {
+        match self.slots.get(key.index)? {
+            Slot::Occupied(value) => Some(value),
+            Slot::Vacant { .. } => None,
+        }
+    }
+
+    /// Returns a mutable reference to the value behind `key`, if it is occupied.
+    pub fn get_mut(&mut self, key: SlabRef<T>) -> Option<&mut T> {
+        match self.slots.get_mut(key.index)? {
+            Slot::Occupied(value) => Some(value),
+            Slot::Vacant { .. } => None,
+        }
+    }
+
+    /// Returns the number of occupied slots.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if no slots are occupied.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the total number of slots.
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+}
+
+// --- Tests ---
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static DROP_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug)]
+    struct Order {
+        id: u64,
+    }
+
+    impl Drop for Order {
+        fn drop(&mut self) {
+            DROP_COUNT.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn test_insert_remove_reuse() {
+        DROP_COUNT.store(0, Ordering::SeqCst);
+        let mut slab = Slab::new(2);
+
+        let a = slab.insert(Order { id: 1 }).unwrap();
+        let b = slab.insert(Order { id: 2 }).unwrap();
+        assert_eq!(slab.len(), 2);
+
+        // Full: the value comes back to us (and is dropped here).
+        let rejected = slab.insert(Order { id: 3 }).unwrap_err();
+        assert_eq!(rejected.id, 3);
+        drop(rejected);
+        assert_eq!(DROP_COUNT.load(Ordering::SeqCst), 1);
+
+        slab.get_mut(b).unwrap().id = 20;
+        assert_eq!(slab.get(b).unwrap().id, 20);
+
+        // Removing hands the value back; dropping it runs its destructor.
+        let removed = slab.remove(a).unwrap();
+        assert_eq!(removed.id, 1);
+        drop(removed);
+        assert_eq!(DROP_COUNT.load(Ordering::SeqCst), 2);
+        assert!(slab.get(a).is_none());
+        assert!(slab.remove(a).is_none());
+
+        // The freed slot is reused.
+        let c = slab.insert(Order { id: 4 }).unwrap();
+        assert_eq!(c.index(), a.index());
+        assert_eq!(slab.len(), 2);
+
+        // Dropping the slab drops whatever is still stored.
+        drop(slab);
+        assert_eq!(DROP_COUNT.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn test_zero_capacity() {
+        let mut slab = Slab::new(0);
+        assert_eq!(slab.insert(1u32), Err(1));
+        assert!(slab.is_empty());
+    }
+}
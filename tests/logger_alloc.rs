@@ -0,0 +1,72 @@
+//! Checks that `Logger::log_fmt` doesn't allocate on the producer thread once
+//! warmed up. Lives in its own test binary because it installs a counting
+//! global allocator.
+
+#![cfg(feature = "logger")]
+
+use llt_rs::logger::Logger;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::{Arc, Mutex};
+
+struct CountingAlloc;
+
+thread_local! {
+    /// Allocations made by *this* thread, so the worker's don't count.
+    static ALLOCATIONS: Cell<u64> = const { Cell::new(0) };
+}
+
+// SAFETY: Forwards to `System`; the counter is a const thread-local `Cell`,
+// which never allocates.
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn allocations() -> u64 {
+    ALLOCATIONS.with(Cell::get)
+}
+
+#[test]
+fn log_fmt_does_not_allocate_in_steady_state() {
+    let captured = Arc::new(Mutex::new(Vec::new()));
+    let sink_captured = captured.clone();
+    let logger = Logger::builder()
+        .capacity(1024)
+        .worker_core(None)
+        .sink(move |msg| sink_captured.lock().unwrap().push(msg))
+        .build();
+
+    // Warm up: the first call on this thread creates its scratch arena.
+    logger.log_fmt(format_args!("warm-up"));
+    logger.flush();
+
+    let before = allocations();
+    for i in 0..500u32 {
+        logger.log_fmt(format_args!(
+            "tick {} px={:.2} side={}",
+            i,
+            f64::from(i) * 0.25,
+            "bid"
+        ));
+    }
+    let allocated = allocations() - before;
+    logger.flush();
+
+    assert_eq!(allocated, 0, "steady-state logging allocated");
+    assert_eq!(logger.get_dropped_count(), 0);
+
+    let got = captured.lock().unwrap();
+    assert_eq!(got.len(), 501);
+    assert_eq!(got[1], "tick 0 px=0.00 side=bid");
+    assert_eq!(got[500], "tick 499 px=124.75 side=bid");
+}
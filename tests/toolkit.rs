@@ -0,0 +1,93 @@
+//! End-to-end check that the modules compose: a pinned producer feeds a
+//! channel, a pinned consumer drains it into an arena, and a logger records
+//! progress from both sides.
+
+#![cfg(all(feature = "logger", feature = "arena_allocator"))]
+
+use llt_rs::RingBuffer;
+use llt_rs::affinity::{self, CoreId};
+use llt_rs::arena_allocator::Arena;
+use llt_rs::channel::channel;
+use llt_rs::logger::Logger;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+const ITEMS: u64 = 50_000;
+const REPORT_EVERY: u64 = 10_000;
+
+#[derive(Clone, Copy)]
+struct Tick {
+    seq: u64,
+    price: u64,
+}
+
+/// Pins the current thread (best effort: the sandbox may have one core).
+fn pin(core: Option<CoreId>) {
+    if let Some(core) = core {
+        let _ = affinity::pin_to_core(core);
+    }
+}
+
+#[test]
+fn test_pinned_pipeline_loses_nothing() {
+    let cores = affinity::get_core_ids();
+    let (producer_core, consumer_core) = (cores.first().copied(), cores.last().copied());
+
+    let lines = Arc::new(Mutex::new(Vec::new()));
+    let sink_lines = lines.clone();
+    let logger = Logger::builder()
+        .capacity(64)
+        .worker_core(None)
+        .sink(move |msg| sink_lines.lock().unwrap().push(msg))
+        .build();
+
+    let (tx, rx) = channel::<Tick>(1024);
+    // The consumer hands its summary back over a bare ring buffer.
+    let results = Arc::new(RingBuffer::<(u64, u64)>::new(1));
+
+    let producer_log = logger.clone();
+    let producer = thread::spawn(move || {
+        pin(producer_core);
+        for seq in 0..ITEMS {
+            tx.send(Tick {
+                seq,
+                price: seq * 2,
+            });
+        }
+        producer_log.log(format!("produced {}", ITEMS));
+        // Dropping `tx` disconnects the channel, ending the consumer's loop.
+    });
+
+    let consumer_log = logger.clone();
+    let consumer_results = results.clone();
+    let consumer = thread::spawn(move || {
+        pin(consumer_core);
+        // The arena is single-threaded, so it lives on the consumer thread.
+        let arena = Arena::new(ITEMS as usize * std::mem::size_of::<Tick>() + 64);
+        let (mut count, mut price_sum) = (0u64, 0u64);
+        while let Some(tick) = rx.recv() {
+            let stored = arena.alloc(tick);
+            assert_eq!(stored.seq, count, "out of order");
+            price_sum += stored.price;
+            count += 1;
+            if count % REPORT_EVERY == 0 {
+                consumer_log.log(format!("consumed {}", count));
+            }
+        }
+        consumer_results.send((count, price_sum)).unwrap();
+    });
+
+    producer.join().unwrap();
+    consumer.join().unwrap();
+    logger.shutdown();
+
+    let (count, price_sum) = results.recv().unwrap();
+    assert_eq!(count, ITEMS);
+    assert_eq!(price_sum, ITEMS * (ITEMS - 1));
+
+    let lines = lines.lock().unwrap();
+    assert_eq!(logger.get_dropped_count(), 0);
+    assert_eq!(lines.len() as u64, 1 + ITEMS / REPORT_EVERY);
+    assert!(lines.contains(&format!("produced {}", ITEMS)));
+    assert!(lines.contains(&format!("consumed {}", ITEMS)));
+}